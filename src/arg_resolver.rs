@@ -1,106 +1,558 @@
-use templates_resolver::resolve_template_args;
+pub use explain::{explain_templates, Explanation};
+use numbers::{Arithmetic, FormatSpec};
+use paths::PathOperation;
+use substitution::Substitution;
+use templates_resolver::{resolve_arg_template, resolve_template_args};
 use thiserror::Error;
 use tokens::LexingError;
 
-use std::str::Split;
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
 
-use crate::cli::Cli;
+use crate::cli::{Cli, CombineMode};
 
+mod explain;
+mod numbers;
+mod paths;
+mod substitution;
 mod templates_resolver;
 mod tokens;
 
-// echo abcd{0}efg{1.0} {2} {}
+// echo abcd{0}efg{1.0} {2} {} {0/2.0} {#} {key} {L0} {S1:0}
 #[derive(Debug, PartialEq, Eq)]
 enum ArgTemplatePart<'a> {
-    //{0}
-    Index(usize),
-    //{0.}
-    IndexSplit(usize, &'a str),
-    //{0.0}
-    IndexSplitIndex(usize, &'a str, usize),
-    //{.0}
-    SplitIndex(&'a str, usize),
-    //{.}
-    Split(&'a str),
-    //{}
-    Empty,
+    //{0.1} is Placeholder(Arg(0), [SplitIndex(".", 1)])
+    //{} is Placeholder(AllArgs, []) which takes all input args
+    Placeholder(ArgSource, Vec<ArgOperation<'a>>),
     //abcd
     FreeText(&'a str),
 }
 
+impl ArgTemplatePart<'_> {
+    //Whether part may resolve to many values, in grouped entries each arg is array of members args
+    fn is_array(&self, grouped: bool) -> bool {
+        let ArgTemplatePart::Placeholder(source, operations) = self else {
+            return false;
+        };
+        let is_source_array = match source {
+            ArgSource::AllArgs => true,
+            ArgSource::Arg(_) | ArgSource::Source(_, Some(_)) => grouped,
+            ArgSource::Source(_, None) => true,
+            ArgSource::EntryNumber | ArgSource::GroupKey | ArgSource::Line(_) => false,
+        };
+        operations
+            .iter()
+            .fold(is_source_array, |is_array, operation| match operation {
+                ArgOperation::Split(_) => true,
+                ArgOperation::SplitIndex(..)
+                | ArgOperation::Slice(..)
+                | ArgOperation::Substitute(_)
+                | ArgOperation::Path(_)
+                | ArgOperation::Arithmetic(_)
+                | ArgOperation::Format(_) => is_array,
+                ArgOperation::Join(_) => false,
+            })
+    }
+}
+
+// Values that placeholder starts with
+#[derive(Debug, PartialEq, Eq)]
+enum ArgSource {
+    //{}
+    AllArgs,
+    //{0}
+    Arg(usize),
+    //{#} - number of entry starting from 1
+    EntryNumber,
+    //{key} - key of entries group
+    GroupKey,
+    //{L0} - whole line of entry made of many lines
+    Line(usize),
+    //{S1} - all args of input source, {S1:0} - single arg of input source, sources are numbered from 1
+    Source(usize, Option<usize>),
+}
+
+// Operations applied in order to values taken by placeholder
+#[derive(Debug, PartialEq, Eq)]
+enum ArgOperation<'a> {
+    //. - each value is split and all parts are taken
+    Split(&'a str),
+    //.0 - each value is split and single part is taken
+    SplitIndex(&'a str, usize),
+    //:stem - each value is treated as path, see PathOperation
+    Path(PathOperation<'a>),
+    //[0:3] - characters of each value, negative positions count from end
+    Slice(Option<isize>, Option<isize>),
+    ///s/a/b/ - matches of regex in each value are replaced
+    Substitute(Substitution),
+    //:+1 - each value is treated as integer
+    Arithmetic(Arithmetic),
+    //:05 - each value is padded to width
+    Format(FormatSpec),
+    //|join:, - all values are joined into single value
+    Join(&'a str),
+}
+
 #[derive(Error, Debug)]
 pub enum ResolveError {
     //TODO: this needs better error handling because we don't display which arg failed
     //so maybe anyhow will be better so we can keep adding context?
     #[error("Index {0} is out of bounds")]
     InvalidIndex(usize),
+    #[error("Template resolved to {0} values while exactly one was expected")]
+    NotSingleValue(usize),
+    #[error("Cannot zip arrays of different lengths {0} and {1}")]
+    ArraysLengthMismatch(usize, usize),
+    #[error("'{0}' is not a number")]
+    NotANumber(String),
+    #[error("Group key is available only when entries are grouped with --group-by")]
+    NoGroupKey,
+    #[error("There is no input source {0}")]
+    NoSource(usize),
+    #[error("Arithmetic overflow for '{0}'")]
+    ArithmeticOverflow(String),
+    #[error("Cannot resolve path '{}': {1}", .0.display())]
+    InvalidPath(PathBuf, io::Error),
     #[error("unknown data store error")]
     Other,
 }
 
-//FIXME:template_args may be empty, it means that we should append all args as last argument
-pub fn resolve_cmd_args(stdin_entries: Vec<String>, cli: &Cli) -> Vec<Vec<String>> {
-    let mut entries = Vec::new();
-    //FIXME: handle error
-    let args_resolver = ArgumentResolver::new(&cli.args_templates).unwrap();
+/// Command arguments resolved for single entry together with its output redirections
+#[derive(Debug, PartialEq, Eq)]
+pub struct ResolvedEntry {
+    /// Entry number starting from 1
+    pub number: usize,
+    pub input: String,
+    pub input_args: Vec<String>,
+    pub args: Vec<String>,
+    pub stdout_to: Option<PathBuf>,
+    pub stderr_to: Option<PathBuf>,
+}
+
+/// Entry that could not be resolved into command
+#[derive(Error, Debug)]
+#[error("Failed to resolve entry {number}: {error}")]
+pub struct EntryError {
+    number: usize,
+    error: ResolveError,
+}
+
+impl EntryError {
+    fn new(number: usize, error: ResolveError) -> EntryError {
+        EntryError { number, error }
+    }
+}
+
+/// Template that could not be parsed
+#[derive(Error, Debug)]
+#[error("Invalid template '{template}': {error}")]
+pub struct TemplateError {
+    template: String,
+    error: LexingError,
+}
+
+/// Input entry, combined from one entry of each input source when many are given, see --values.
+/// Sources are kept apart so input may contain any character, including separators of other sources
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    sources: Vec<String>,
+}
+
+impl Entry {
+    pub fn combined(sources: Vec<String>) -> Entry {
+        Entry { sources }
+    }
+
+    /// Input of all sources, each one starting in new line
+    pub fn input(&self) -> String {
+        self.sources.join("\n")
+    }
+}
+
+impl From<String> for Entry {
+    fn from(input: String) -> Entry {
+        Entry {
+            sources: vec![input],
+        }
+    }
+}
+
+impl From<&str> for Entry {
+    fn from(input: &str) -> Entry {
+        Entry::from(input.to_string())
+    }
+}
+
+/// Args of entry that placeholders are resolved from. Entry made of many lines,
+/// e.g. with --lines, is split into lines first and then each line into args
+pub(crate) struct EntryArgs<'a> {
+    number: usize,
+    //Args of each source of each grouped entry, entry that is not grouped is the only member
+    //and entry that was not combined from many sources has one source
+    members: Vec<Vec<Vec<&'a str>>>,
+    lines: Vec<&'a str>,
+    key: Option<&'a str>,
+}
+
+impl<'a> EntryArgs<'a> {
+    pub(crate) fn new(number: usize, entry: &'a Entry, args_separator: &str) -> EntryArgs<'a> {
+        EntryArgs {
+            number,
+            members: vec![split_sources(entry, args_separator)],
+            lines: split_lines(entry).collect(),
+            key: None,
+        }
+    }
+
+    fn group(
+        number: usize,
+        key: &'a str,
+        members: &'a [Entry],
+        args_separator: &str,
+    ) -> EntryArgs<'a> {
+        EntryArgs {
+            number,
+            members: members
+                .iter()
+                .map(|member| split_sources(member, args_separator))
+                .collect(),
+            lines: members.iter().flat_map(split_lines).collect(),
+            key: Some(key),
+        }
+    }
+
+    pub(crate) fn args_count(&self) -> usize {
+        self.members
+            .iter()
+            .flatten()
+            .map(|source| source.len())
+            .sum()
+    }
+
+    fn all_args(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.members.iter().flatten().flatten().copied()
+    }
+}
+
+fn split_sources<'a>(entry: &'a Entry, args_separator: &str) -> Vec<Vec<&'a str>> {
+    entry
+        .sources
+        .iter()
+        .map(|source| split_args(source, args_separator))
+        .collect()
+}
 
-    for stdin_entry in stdin_entries {
-        let input_args = stdin_entry
-            .split(&cli.args_separator)
-            .collect::<Vec<&str>>();
-        //FIXME: handle error
-        //we may add flag to choose how to behave on error like:
-        //panic and break
-        //replace invalid value withempty string
-        //ignore failed entry and continue with others
-        let entry = args_resolver.resolve(input_args).unwrap();
-        entries.push(entry);
+fn split_lines(entry: &Entry) -> impl Iterator<Item = &str> {
+    entry.sources.iter().flat_map(|source| source.split('\n'))
+}
+
+fn split_args<'a>(input: &'a str, args_separator: &str) -> Vec<&'a str> {
+    input
+        .split('\n')
+        .flat_map(|line| line.split(args_separator))
+        .collect()
+}
+
+/// Entries with the same key, resolved into single command
+struct EntryGroup {
+    key: String,
+    members: Vec<Entry>,
+}
+
+/// Resolves entries into command arguments and output paths. Templates are parsed
+/// when resolver is created so invalid ones are reported before any input is read
+pub struct CmdResolver<'a> {
+    args_resolver: ArgumentResolver<'a>,
+    stdout_resolver: Option<ArgumentResolver<'a>>,
+    stderr_resolver: Option<ArgumentResolver<'a>>,
+    group_resolver: Option<ArgumentResolver<'a>>,
+    args_separator: &'a str,
+}
+
+impl<'a> CmdResolver<'a> {
+    pub fn new(cli: &'a Cli) -> Result<CmdResolver<'a>, TemplateError> {
+        let grouped = cli.group_by.is_some();
+        let args_resolver = ArgumentResolver::new(&cli.args_templates, cli.combine)?;
+        if !grouped {
+            reject_group_key(&args_resolver, &cli.args_templates)?;
+        }
+        Ok(CmdResolver {
+            args_resolver,
+            stdout_resolver: single_value_resolver(&cli.stdout_to, cli.combine, grouped)?,
+            stderr_resolver: single_value_resolver(&cli.stderr_to, cli.combine, grouped)?,
+            group_resolver: single_value_resolver(&cli.group_by, cli.combine, false)?,
+            args_separator: &cli.args_separator,
+        })
+    }
+
+    /// Resolves all entries, fails on first entry that cannot be resolved so no command is run
+    pub fn resolve_entries(
+        &self,
+        stdin_entries: Vec<Entry>,
+    ) -> Result<Vec<ResolvedEntry>, EntryError> {
+        if let Some(group_resolver) = &self.group_resolver {
+            return self
+                .group_entries(group_resolver, stdin_entries)?
+                .into_iter()
+                .enumerate()
+                .map(|(idx, group)| {
+                    self.resolve_group(idx + 1, group)
+                        .map_err(|error| EntryError::new(idx + 1, error))
+                })
+                .collect();
+        }
+        stdin_entries
+            .into_iter()
+            .enumerate()
+            .map(|(idx, stdin_entry)| {
+                self.resolve_entry(idx + 1, stdin_entry)
+                    .map_err(|error| EntryError::new(idx + 1, error))
+            })
+            .collect()
+    }
+
+    /// Resolves single entry, when entries are grouped it is resolved as group of one
+    pub fn resolve_entry(
+        &self,
+        number: usize,
+        entry: Entry,
+    ) -> Result<ResolvedEntry, ResolveError> {
+        let entry_args = EntryArgs::new(number, &entry, self.args_separator);
+        if let Some(group_resolver) = &self.group_resolver {
+            let key = group_resolver.resolve_single(&entry_args)?;
+            let members = vec![entry];
+            return self.resolve_group(number, EntryGroup { key, members });
+        }
+        let resolved = self.resolve_entry_args(&entry_args)?;
+        Ok(resolved.into_entry(number, entry.input()))
+    }
+
+    //Groups are ordered by first entry with given key
+    fn group_entries(
+        &self,
+        group_resolver: &ArgumentResolver,
+        entries: Vec<Entry>,
+    ) -> Result<Vec<EntryGroup>, EntryError> {
+        let mut groups: Vec<EntryGroup> = Vec::new();
+        let mut group_indexes: HashMap<String, usize> = HashMap::new();
+        for (idx, entry) in entries.into_iter().enumerate() {
+            let entry_args = EntryArgs::new(idx + 1, &entry, self.args_separator);
+            let key = group_resolver
+                .resolve_single(&entry_args)
+                .map_err(|error| EntryError::new(idx + 1, error))?;
+            match group_indexes.get(&key) {
+                Some(&group_idx) => groups[group_idx].members.push(entry),
+                None => {
+                    group_indexes.insert(key.clone(), groups.len());
+                    groups.push(EntryGroup {
+                        key,
+                        members: vec![entry],
+                    });
+                }
+            }
+        }
+        Ok(groups)
+    }
+
+    fn resolve_group(
+        &self,
+        number: usize,
+        group: EntryGroup,
+    ) -> Result<ResolvedEntry, ResolveError> {
+        let entry_args = EntryArgs::group(number, &group.key, &group.members, self.args_separator);
+        let resolved = self.resolve_entry_args(&entry_args)?;
+        let input = group
+            .members
+            .iter()
+            .map(|member| member.input())
+            .collect::<Vec<String>>()
+            .join("\n");
+        Ok(resolved.into_entry(number, input))
+    }
+
+    fn resolve_entry_args(&self, entry_args: &EntryArgs) -> Result<ResolvedArgs, ResolveError> {
+        Ok(ResolvedArgs {
+            input_args: entry_args.all_args().map(|a| a.to_string()).collect(),
+            args: self.args_resolver.resolve(entry_args)?,
+            stdout_to: resolve_path(&self.stdout_resolver, entry_args)?,
+            stderr_to: resolve_path(&self.stderr_resolver, entry_args)?,
+        })
+    }
+}
+
+//Resolved parts of entry, kept apart from input which they borrow from
+struct ResolvedArgs {
+    input_args: Vec<String>,
+    args: Vec<String>,
+    stdout_to: Option<PathBuf>,
+    stderr_to: Option<PathBuf>,
+}
+
+impl ResolvedArgs {
+    fn into_entry(self, number: usize, input: String) -> ResolvedEntry {
+        ResolvedEntry {
+            number,
+            input,
+            input_args: self.input_args,
+            args: self.args,
+            stdout_to: self.stdout_to,
+            stderr_to: self.stderr_to,
+        }
     }
-    entries
+}
+
+//Templates of paths and keys must resolve into exactly one value for every entry
+fn single_value_resolver(
+    template: &Option<String>,
+    combine: CombineMode,
+    grouped: bool,
+) -> Result<Option<ArgumentResolver<'_>>, TemplateError> {
+    let Some(template) = template else {
+        return Ok(None);
+    };
+    let resolver = ArgumentResolver::for_template(template, combine)?;
+    if !grouped {
+        reject_group_key(&resolver, std::slice::from_ref(template))?;
+    }
+    if resolver.is_array(grouped) {
+        return Err(TemplateError {
+            template: template.clone(),
+            error: LexingError::NotSingleValue,
+        });
+    }
+    Ok(Some(resolver))
+}
+
+//Group key of entries grouped with --group-by, elsewhere it could not be resolved for any entry
+fn reject_group_key(
+    resolver: &ArgumentResolver,
+    templates: &[String],
+) -> Result<(), TemplateError> {
+    let uses_group_key = |arg_template: &ResolvedArgument| {
+        arg_template
+            .iter()
+            .any(|part| matches!(part, ArgTemplatePart::Placeholder(ArgSource::GroupKey, _)))
+    };
+    match resolver.resolved_args.iter().position(uses_group_key) {
+        Some(idx) => Err(TemplateError {
+            template: templates[idx].clone(),
+            error: LexingError::NoGroupKey,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn resolve_path(
+    resolver: &Option<ArgumentResolver>,
+    entry_args: &EntryArgs,
+) -> Result<Option<PathBuf>, ResolveError> {
+    resolver
+        .as_ref()
+        .map(|r| r.resolve_single(entry_args).map(PathBuf::from))
+        .transpose()
 }
 
 type ResolvedArgument<'a> = Vec<ArgTemplatePart<'a>>;
 
-struct ArgumentResolver<'a> {
+pub(crate) struct ArgumentResolver<'a> {
     resolved_args: Vec<ResolvedArgument<'a>>,
     has_any_placeholder: bool,
+    combine: CombineMode,
 }
 
 impl<'a> ArgumentResolver<'a> {
-    fn new(arg_templates: &'a [String]) -> Result<ArgumentResolver<'a>, LexingError> {
+    fn new(
+        arg_templates: &'a [String],
+        combine: CombineMode,
+    ) -> Result<ArgumentResolver<'a>, TemplateError> {
         let resolved_args = resolve_template_args(arg_templates)?;
+        Ok(Self::from_resolved_args(resolved_args, combine))
+    }
+
+    /// Resolver of single template that is part of other option, e.g. filter expression
+    pub(crate) fn for_template(
+        arg_template: &'a str,
+        combine: CombineMode,
+    ) -> Result<ArgumentResolver<'a>, TemplateError> {
+        let resolved_arg = resolve_arg_template(arg_template).map_err(|error| TemplateError {
+            template: arg_template.to_string(),
+            error,
+        })?;
+        Ok(Self::from_resolved_args(vec![resolved_arg], combine))
+    }
+
+    fn from_resolved_args(
+        resolved_args: Vec<ResolvedArgument<'a>>,
+        combine: CombineMode,
+    ) -> ArgumentResolver<'a> {
         let has_any_placeholder = resolved_args.iter().any(|arg_template| {
             arg_template
                 .iter()
                 .any(|part| !matches!(part, ArgTemplatePart::FreeText(_)))
         });
-        Ok(ArgumentResolver {
+        ArgumentResolver {
             resolved_args,
             has_any_placeholder,
+            combine,
+        }
+    }
+
+    /// Whether any template uses entry number {#}
+    pub(crate) fn uses_entry_number(&self) -> bool {
+        self.resolved_args.iter().flatten().any(|part| {
+            matches!(
+                part,
+                ArgTemplatePart::Placeholder(ArgSource::EntryNumber, _)
+            )
         })
     }
 
-    fn resolve(&self, input_args: Vec<&str>) -> Result<Vec<String>, ResolveError> {
+    fn is_array(&self, grouped: bool) -> bool {
+        self.resolved_args
+            .iter()
+            .flatten()
+            .any(|part| part.is_array(grouped))
+    }
+
+    fn resolve(&self, entry_args: &EntryArgs) -> Result<Vec<String>, ResolveError> {
+        let mut result = self.resolve_templates(entry_args)?;
+        if !self.has_any_placeholder {
+            result.extend(entry_args.all_args().map(|a| a.to_string()));
+        }
+        Ok(result)
+    }
+
+    //Resolves templates into exactly one value, input args are never appended here
+    pub(crate) fn resolve_single(&self, entry_args: &EntryArgs) -> Result<String, ResolveError> {
+        let mut result = self.resolve_templates(entry_args)?;
+        match result.len() {
+            1 => Ok(result.remove(0)),
+            len => Err(ResolveError::NotSingleValue(len)),
+        }
+    }
+
+    fn resolve_templates(&self, entry_args: &EntryArgs) -> Result<Vec<String>, ResolveError> {
         let mut result = Vec::new();
         for arg_template in &self.resolved_args {
-            let mut resolved = self.resolve_arg_template(arg_template, &input_args)?;
+            let mut resolved = self.resolve_arg_template(arg_template, entry_args)?;
             result.append(&mut resolved);
         }
-        if !self.has_any_placeholder {
-            result.append(&mut input_args.iter().map(|a| a.to_string()).collect());
-        }
         Ok(result)
     }
 
     fn resolve_arg_template(
         &self,
         arg_template: &[ArgTemplatePart],
-        input_args: &[&str],
+        entry_args: &EntryArgs,
     ) -> Result<Vec<String>, ResolveError> {
         let mut resolved = Vec::new();
         for part in arg_template {
-            let single_part = resolve_single_arg_part(part, input_args)?;
-            resolved = multiply_args_parts(resolved, single_part);
+            let single_part = resolve_single_arg_part(part, entry_args)?;
+            resolved = match self.combine {
+                CombineMode::Cartesian => multiply_args_parts(resolved, single_part),
+                CombineMode::Zip => zip_args_parts(resolved, single_part)?,
+            };
         }
         Ok(resolved)
     }
@@ -108,33 +560,80 @@ impl<'a> ArgumentResolver<'a> {
 
 fn resolve_single_arg_part(
     arg_template: &ArgTemplatePart,
-    input_args: &[&str],
+    entry_args: &EntryArgs,
 ) -> Result<Vec<String>, ResolveError> {
-    let resolved = match arg_template {
-        ArgTemplatePart::Index(idx) => vec![get_input_arg(*idx, input_args)?.to_string()],
-        ArgTemplatePart::IndexSplit(idx, split_by) => {
-            let input_arg = get_input_arg(*idx, input_args)?;
-            input_arg.split(*split_by).map(|s| s.to_string()).collect()
-        }
-        ArgTemplatePart::IndexSplitIndex(idx, split_by, split_idx) => {
-            let input_arg = get_input_arg(*idx, input_args)?;
-            let mut splitted = input_arg.split(*split_by);
-            vec![get_split_arg(*split_idx, &mut splitted)?.to_string()]
-        }
-        ArgTemplatePart::SplitIndex(split_by, split_idx) => input_args
+    let (source, operations) = match arg_template {
+        ArgTemplatePart::Placeholder(source, operations) => (source, operations),
+        ArgTemplatePart::FreeText(text) => return Ok(vec![text.to_string()]),
+    };
+    let mut resolved = match source {
+        ArgSource::AllArgs => entry_args.all_args().map(|a| a.to_string()).collect(),
+        //Grouped entries give array with arg of each member
+        ArgSource::Arg(idx) => entry_args
+            .members
             .iter()
-            .map(|a| {
-                let mut splitted = a.split(*split_by);
-                get_split_arg(*split_idx, &mut splitted).map(|s| s.to_string())
+            .map(|sources| {
+                let arg = sources.iter().flatten().nth(*idx);
+                arg.map(|a| a.to_string())
+                    .ok_or(ResolveError::InvalidIndex(*idx))
             })
             .collect::<Result<Vec<String>, ResolveError>>()?,
-        ArgTemplatePart::Split(split_by) => input_args
+        ArgSource::EntryNumber => vec![entry_args.number.to_string()],
+        ArgSource::GroupKey => vec![entry_args.key.ok_or(ResolveError::NoGroupKey)?.to_string()],
+        ArgSource::Line(idx) => vec![get_input_arg(*idx, &entry_args.lines)?.to_string()],
+        ArgSource::Source(number, idx) => {
+            let mut resolved = Vec::new();
+            for sources in &entry_args.members {
+                let source = sources
+                    .get(number - 1)
+                    .ok_or(ResolveError::NoSource(*number))?;
+                match idx {
+                    Some(idx) => resolved.push(get_input_arg(*idx, source)?.to_string()),
+                    None => resolved.extend(source.iter().map(|a| a.to_string())),
+                }
+            }
+            resolved
+        }
+    };
+    for operation in operations {
+        resolved = apply_operation(operation, resolved)?;
+    }
+    Ok(resolved)
+}
+
+fn apply_operation(
+    operation: &ArgOperation,
+    values: Vec<String>,
+) -> Result<Vec<String>, ResolveError> {
+    let resolved = match operation {
+        ArgOperation::Split(split_by) => values
+            .iter()
+            .flat_map(|v| v.split(split_by).map(|s| s.to_string()))
+            .collect(),
+        ArgOperation::SplitIndex(split_by, split_idx) => values
+            .iter()
+            .map(|v| get_split_arg(*split_idx, v, split_by).map(|s| s.to_string()))
+            .collect::<Result<Vec<String>, ResolveError>>()?,
+        ArgOperation::Slice(start, end) => values
+            .iter()
+            .map(|v| slice_chars(v, *start, *end))
+            .collect(),
+        ArgOperation::Substitute(substitution) => {
+            values.iter().map(|v| substitution.apply(v)).collect()
+        }
+        ArgOperation::Path(operation) => values
+            .iter()
+            .map(|v| operation.apply(v))
+            .collect::<Result<Vec<String>, ResolveError>>()?,
+        ArgOperation::Arithmetic(arithmetic) => values
+            .iter()
+            .map(|v| arithmetic.apply(v))
+            .collect::<Result<Vec<String>, ResolveError>>()?,
+        ArgOperation::Format(spec) => values
             .iter()
-            .map(|a| a.split(split_by).map(|s| s.to_string()))
-            .flat_map(|a| a.into_iter())
-            .collect::<Vec<String>>(),
-        ArgTemplatePart::Empty => input_args.iter().map(|a| a.to_string()).collect(),
-        ArgTemplatePart::FreeText(text) => vec![text.to_string()],
+            .map(|v| spec.apply(v))
+            .collect::<Result<Vec<String>, ResolveError>>()?,
+        ArgOperation::Join(join_by) => vec![values.join(join_by)],
     };
     Ok(resolved)
 }
@@ -146,11 +645,30 @@ fn get_input_arg<'a>(idx: usize, input_args: &'a [&'a str]) -> Result<&'a str, R
         .ok_or(ResolveError::InvalidIndex(idx))
 }
 
-fn get_split_arg<'a>(
-    idx: usize,
-    splitted: &'a mut Split<&'a str>,
-) -> Result<&'a str, ResolveError> {
-    splitted.nth(idx).ok_or(ResolveError::InvalidIndex(idx))
+fn get_split_arg<'a>(idx: usize, value: &'a str, split_by: &str) -> Result<&'a str, ResolveError> {
+    value
+        .split(split_by)
+        .nth(idx)
+        .ok_or(ResolveError::InvalidIndex(idx))
+}
+
+//Positions are clamped to value length like in Python slices so it never fails
+fn slice_chars(value: &str, start: Option<isize>, end: Option<isize>) -> String {
+    let len = value.chars().count() as isize;
+    let position = |pos: isize| {
+        if pos < 0 {
+            (len + pos).max(0)
+        } else {
+            pos.min(len)
+        }
+    };
+    let start = start.map_or(0, position);
+    let end = end.map_or(len, position);
+    value
+        .chars()
+        .skip(start as usize)
+        .take((end - start).max(0) as usize)
+        .collect()
 }
 
 //This performs args multiplication for example
@@ -173,3 +691,240 @@ fn multiply_args_parts(a: Vec<String>, b: Vec<String>) -> Vec<String> {
 
     result
 }
+
+//This pairs args element-wise, single values are paired with each element of other array
+//[a,b] + [c] -> [ac,bc]
+//[a] + [b] -> [ab]
+//[a,b] + [c,d] -> [ac,bd]
+//[a,b] + [c,d,e] -> error
+fn zip_args_parts(a: Vec<String>, b: Vec<String>) -> Result<Vec<String>, ResolveError> {
+    if a.is_empty() {
+        return Ok(b);
+    }
+    if b.is_empty() {
+        return Ok(a);
+    }
+    let resolved = match (a.len(), b.len()) {
+        (1, _) => b
+            .iter()
+            .map(|b_part| format!("{}{}", a[0], b_part))
+            .collect(),
+        (_, 1) => a
+            .iter()
+            .map(|a_part| format!("{}{}", a_part, b[0]))
+            .collect(),
+        (a_len, b_len) if a_len == b_len => a
+            .iter()
+            .zip(b.iter())
+            .map(|(a_part, b_part)| format!("{}{}", a_part, b_part))
+            .collect(),
+        (a_len, b_len) => return Err(ResolveError::ArraysLengthMismatch(a_len, b_len)),
+    };
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn should_resolve_template_into_single_value() {
+        let templates = vec!["out/{0.0}.log".to_string()];
+        let resolver = ArgumentResolver::new(&templates, CombineMode::Cartesian).unwrap();
+        assert_eq!(
+            "out/a.log",
+            resolver
+                .resolve_single(&EntryArgs::new(1, &Entry::from("a.txt b.txt"), " "))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_fail_to_resolve_template_into_single_value_when_many_resolved() {
+        let templates = vec!["out/{}.log".to_string()];
+        let resolver = ArgumentResolver::new(&templates, CombineMode::Cartesian).unwrap();
+        assert!(matches!(
+            resolver
+                .resolve_single(&EntryArgs::new(1, &Entry::from("a b"), " "))
+                .unwrap_err(),
+            ResolveError::NotSingleValue(2)
+        ));
+    }
+
+    #[test]
+    fn should_slice_characters() {
+        assert_eq!("202", slice_chars("2024-01-05_a", Some(0), Some(3)));
+        assert_eq!("05_a", slice_chars("2024-01-05_a", Some(-4), None));
+        assert_eq!("żół", slice_chars("zażółć", Some(2), Some(-1)));
+        assert_eq!("", slice_chars("abc", Some(2), Some(1)));
+        assert_eq!("abc", slice_chars("abc", Some(-10), Some(10)));
+    }
+
+    #[test]
+    fn should_resolve_one_entry_per_group() {
+        let cli = Cli::parse_from(["axe", "--group-by", "{0}", "tar", "{key}.tgz", "{1}"]);
+        let resolver = CmdResolver::new(&cli).unwrap();
+        let entries = ["h1 a.log", "h2 b.log", "h1 c.log"];
+        let resolved = resolver
+            .resolve_entries(entries.iter().map(|e| Entry::from(*e)).collect())
+            .unwrap();
+        assert_eq!(2, resolved.len());
+        assert_eq!(vec!["h1.tgz", "a.log", "c.log"], resolved[0].args);
+        assert_eq!("h1 a.log\nh1 c.log", resolved[0].input);
+        assert_eq!(vec!["h1", "a.log", "h1", "c.log"], resolved[0].input_args);
+        assert_eq!(vec!["h2.tgz", "b.log"], resolved[1].args);
+        assert_eq!(2, resolved[1].number);
+    }
+
+    #[test]
+    fn should_resolve_whole_lines_of_entry() {
+        let templates = vec!["{L1.0}".to_string(), "{1}".to_string()];
+        let resolver = ArgumentResolver::new(&templates, CombineMode::Cartesian).unwrap();
+        let entry = Entry::from("a b\nc.x d");
+        let entry_args = EntryArgs::new(1, &entry, " ");
+        assert_eq!(vec!["c", "b"], resolver.resolve(&entry_args).unwrap());
+        assert!(matches!(
+            ArgumentResolver::for_template("{L2}", CombineMode::Cartesian)
+                .unwrap()
+                .resolve(&entry_args)
+                .unwrap_err(),
+            ResolveError::InvalidIndex(2)
+        ));
+    }
+
+    #[test]
+    fn should_fail_to_resolve_entries_with_number_of_failed_entry() {
+        let cli = Cli::parse_from(["axe", "echo", "{1}"]);
+        let resolver = CmdResolver::new(&cli).unwrap();
+        let error = resolver
+            .resolve_entries(vec![Entry::from("a b"), Entry::from("c")])
+            .unwrap_err();
+        assert_eq!(2, error.number);
+        assert!(matches!(error.error, ResolveError::InvalidIndex(1)));
+    }
+
+    #[test]
+    fn should_reject_path_templates_resolving_to_many_values() {
+        let new_resolver = |args: &[&str]| {
+            let cli = Cli::parse_from([&["axe"], args, &["echo"]].concat());
+            CmdResolver::new(&cli).map(|_| ()).map_err(|e| e.error)
+        };
+        assert_eq!(
+            Err(LexingError::NotSingleValue),
+            new_resolver(&["--stdout-to", "out/{}.log"])
+        );
+        assert_eq!(
+            Err(LexingError::NotSingleValue),
+            new_resolver(&["--stderr-to", "{0.}.log"])
+        );
+        assert_eq!(
+            Err(LexingError::NotSingleValue),
+            new_resolver(&["--group-by", "{0}", "--stdout-to", "{0}.log"])
+        );
+        assert_eq!(Ok(()), new_resolver(&["--stdout-to", "{0}/{|join:_}.log"]));
+        assert_eq!(
+            Ok(()),
+            new_resolver(&["--group-by", "{0}", "--stdout-to", "{key}.log"])
+        );
+    }
+
+    #[test]
+    fn should_fail_to_group_entry_which_key_cannot_be_resolved() {
+        let cli = Cli::parse_from(["axe", "--group-by", "{2}", "tar", "{key}.tgz", "{1}"]);
+        let resolver = CmdResolver::new(&cli).unwrap();
+        let error = resolver
+            .resolve_entries(vec![Entry::from("h1 a.log 1"), Entry::from("h2 b.log")])
+            .unwrap_err();
+        assert_eq!(2, error.number);
+        assert!(matches!(error.error, ResolveError::InvalidIndex(2)));
+    }
+
+    #[test]
+    fn should_reject_group_key_when_not_grouped() {
+        let new_resolver = |args: &[&str]| {
+            let cli = Cli::parse_from([&["axe"], args].concat());
+            CmdResolver::new(&cli).map(|_| ()).map_err(|e| e.error)
+        };
+        assert_eq!(
+            Err(LexingError::NoGroupKey),
+            new_resolver(&["tar", "{key}.tgz", "{0}"])
+        );
+        assert_eq!(
+            Err(LexingError::NoGroupKey),
+            new_resolver(&["--stdout-to", "{key}.log", "echo"])
+        );
+        assert_eq!(
+            Err(LexingError::NoGroupKey),
+            new_resolver(&["--group-by", "{key}", "echo"])
+        );
+    }
+
+    #[test]
+    fn should_resolve_args_of_input_sources() {
+        let templates = vec!["{S2:1}".to_string(), "{S1}".to_string(), "{L1}".to_string()];
+        let resolver = ArgumentResolver::new(&templates, CombineMode::Cartesian).unwrap();
+        let entry = Entry::combined(vec!["a b\nc".to_string(), "x y".to_string()]);
+        let entry_args = EntryArgs::new(1, &entry, " ");
+        assert_eq!(
+            vec!["y", "a", "b", "c", "c"],
+            resolver.resolve(&entry_args).unwrap()
+        );
+        assert!(matches!(
+            ArgumentResolver::for_template("{S3}", CombineMode::Cartesian)
+                .unwrap()
+                .resolve(&entry_args)
+                .unwrap_err(),
+            ResolveError::NoSource(3)
+        ));
+    }
+
+    #[test]
+    fn should_fail_to_resolve_group_key_when_not_grouped() {
+        let templates = vec!["{key}".to_string()];
+        let resolver = ArgumentResolver::new(&templates, CombineMode::Cartesian).unwrap();
+        assert!(matches!(
+            resolver
+                .resolve(&EntryArgs::new(1, &Entry::from("a"), " "))
+                .unwrap_err(),
+            ResolveError::NoGroupKey
+        ));
+    }
+
+    #[test]
+    fn should_multiply_args_parts() {
+        let templates = vec!["{.0}-{.1}".to_string()];
+        let resolver = ArgumentResolver::new(&templates, CombineMode::Cartesian).unwrap();
+        assert_eq!(
+            vec!["a-x", "a-y", "b-x", "b-y"],
+            resolver
+                .resolve(&EntryArgs::new(1, &Entry::from("a.x b.y"), " "))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_zip_args_parts() {
+        let templates = vec!["mv".to_string(), "{.0}-{.1}".to_string()];
+        let resolver = ArgumentResolver::new(&templates, CombineMode::Zip).unwrap();
+        assert_eq!(
+            vec!["mv", "a-x", "b-y"],
+            resolver
+                .resolve(&EntryArgs::new(1, &Entry::from("a.x b.y"), " "))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_fail_to_zip_arrays_of_different_lengths() {
+        let templates = vec!["{0.}{1.}".to_string()];
+        let resolver = ArgumentResolver::new(&templates, CombineMode::Zip).unwrap();
+        assert!(matches!(
+            resolver
+                .resolve(&EntryArgs::new(1, &Entry::from("a.b c.d.e"), " "))
+                .unwrap_err(),
+            ResolveError::ArraysLengthMismatch(2, 3)
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt::Write;
+
+use logos::Logos;
+
+use super::templates_resolver::resolve_arg_template;
+use super::tokens::{ArgPlaceholderToken, ArgTemplateToken};
+use super::{ArgOperation, ArgSource, ArgTemplatePart};
+use crate::cli::CombineMode;
+
+/// Description of templates, invalid templates are described too
+pub struct Explanation {
+    pub text: String,
+    pub all_valid: bool,
+}
+
+/// Describes how templates are tokenized, into which parts they are resolved
+/// and how these parts are combined into command arguments
+pub fn explain_templates(arg_templates: &[String], combine: CombineMode) -> Explanation {
+    let mut explanation = String::new();
+    let mut has_any_placeholder = false;
+    let mut all_valid = true;
+    for arg_template in arg_templates {
+        match explain_template(&mut explanation, arg_template, combine) {
+            Some(has_placeholder) => has_any_placeholder |= has_placeholder,
+            None => all_valid = false,
+        }
+    }
+    if all_valid && !has_any_placeholder {
+        explanation
+            .push_str("No placeholders used, all input args are appended after these arguments\n");
+    }
+    Explanation {
+        text: explanation,
+        all_valid,
+    }
+}
+
+//Returns whether template contains any placeholder or None if template is invalid
+fn explain_template(out: &mut String, arg_template: &str, combine: CombineMode) -> Option<bool> {
+    writeln!(out, "Template: {}", arg_template).unwrap();
+    writeln!(out, "  Tokens:").unwrap();
+    let mut lex = ArgTemplateToken::lexer(arg_template);
+    while let Some(token) = lex.next() {
+        match token {
+            Ok(ArgTemplateToken::ArgPlaceholder) => writeln!(
+                out,
+                "    ArgPlaceholder {:?}: {}",
+                lex.slice(),
+                placeholder_tokens(lex.slice())
+            ),
+            Ok(token) => writeln!(out, "    {:?} {:?}", token, lex.slice()),
+            Err(error) => writeln!(out, "    {:?} at {:?}", error, lex.slice()),
+        }
+        .unwrap();
+    }
+    let parts = match resolve_arg_template(arg_template) {
+        Ok(parts) => parts,
+        Err(error) => {
+            writeln!(out, "  Invalid template: {}\n", error).unwrap();
+            return None;
+        }
+    };
+    writeln!(out, "  Parts:").unwrap();
+    for part in &parts {
+        writeln!(out, "    {:?} -> {}", part, describe_part_values(part)).unwrap();
+    }
+    writeln!(out, "  Combination:").unwrap();
+    writeln!(out, "    {}\n", describe_combination(&parts, combine)).unwrap();
+    Some(
+        parts
+            .iter()
+            .any(|part| !matches!(part, ArgTemplatePart::FreeText(_))),
+    )
+}
+
+fn placeholder_tokens(placeholder: &str) -> String {
+    ArgPlaceholderToken::lexer(placeholder)
+        .map(|token| match token {
+            Ok(token) => format!("{:?}", token),
+            Err(error) => format!("{:?}", error),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn describe_part_values(part: &ArgTemplatePart) -> String {
+    let (source, operations) = match part {
+        ArgTemplatePart::FreeText(_) => return "text as is".to_string(),
+        ArgTemplatePart::Placeholder(source, operations) => (source, operations),
+    };
+    let mut steps = vec![match source {
+        ArgSource::AllArgs => "all input args".to_string(),
+        ArgSource::Arg(index) => format!("input arg {}", index),
+        ArgSource::EntryNumber => "entry number".to_string(),
+        ArgSource::GroupKey => "group key".to_string(),
+        ArgSource::Line(index) => format!("input line {}", index),
+        ArgSource::Source(number, None) => format!("all args of input source {}", number),
+        ArgSource::Source(number, Some(index)) => {
+            format!("arg {} of input source {}", index, number)
+        }
+    }];
+    steps.extend(operations.iter().map(|operation| match operation {
+        ArgOperation::Split(sep) => format!("split by {:?} taking all parts", sep),
+        ArgOperation::SplitIndex(sep, index) => format!("split by {:?} taking part {}", sep, index),
+        ArgOperation::Substitute(substitution) => format!(
+            "matches of {:?} replaced with {:?}",
+            substitution.pattern(),
+            substitution.replacement()
+        ),
+        ArgOperation::Slice(start, end) => format!("characters from {:?} to {:?}", start, end),
+        ArgOperation::Path(operation) => format!("path operation {:?}", operation),
+        ArgOperation::Arithmetic(arithmetic) => format!("arithmetic {:?}", arithmetic),
+        ArgOperation::Format(spec) => format!("formatted {:?}", spec),
+        ArgOperation::Join(sep) => format!("joined by {:?}", sep),
+    }));
+    let values = if part.is_array(false) {
+        "array"
+    } else {
+        "single value"
+    };
+    format!("{}: {}", steps.join(", "), values)
+}
+
+fn describe_combination(parts: &[ArgTemplatePart], combine: CombineMode) -> String {
+    let arrays = parts.iter().filter(|part| part.is_array(false)).count();
+    match (parts.len(), arrays) {
+        (0, _) => "empty template, no argument".to_string(),
+        (1, 0) => "single argument".to_string(),
+        (1, _) => "each array value becomes separate argument".to_string(),
+        (_, 0) => "parts are concatenated into single argument".to_string(),
+        (_, 1) => "each array value is concatenated with other parts, producing separate argument per array value".to_string(),
+        (_, arrays) if combine == CombineMode::Zip => format!(
+            "{} arrays are paired element-wise and must have equal lengths, each pair is concatenated \
+             with other parts into separate argument, e.g. [a,b] and [c,d] produce ac bd",
+            arrays
+        ),
+        (_, arrays) => format!(
+            "cartesian product of {} arrays, each combination is concatenated with other parts into separate argument, \
+             e.g. [a,b] and [c,d] produce ac ad bc bd",
+            arrays
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_explain_template_with_array_next_to_free_text() {
+        let expected = r#"Template: x{0.}
+  Tokens:
+    FreeText "x"
+    ArgPlaceholder "{0.}": BraceOpen, Index(0), Separator("."), BraceClose
+  Parts:
+    FreeText("x") -> text as is
+    Placeholder(Arg(0), [Split(".")]) -> input arg 0, split by "." taking all parts: array
+  Combination:
+    each array value is concatenated with other parts, producing separate argument per array value
+
+"#;
+        assert_eq!(
+            expected,
+            explain_templates(&["x{0.}".to_string()], CombineMode::Cartesian).text
+        );
+    }
+
+    #[test]
+    fn should_explain_invalid_template() {
+        let expected = r#"Template: {0''}
+  Tokens:
+    ArgPlaceholder "{0''}": BraceOpen, Index(0), InvalidDefinition, BraceClose
+  Invalid template: invalid placeholder definition
+
+"#;
+        let explanation = explain_templates(&["{0''}".to_string()], CombineMode::Cartesian);
+        assert_eq!(expected, explanation.text);
+        assert!(!explanation.all_valid);
+    }
+
+    #[test]
+    fn should_explain_nested_splitting() {
+        let expected = r#"Template: {0/2.0}
+  Tokens:
+    ArgPlaceholder "{0/2.0}": BraceOpen, Index(0), Separator("/"), Index(2), Separator("."), Index(0), BraceClose
+  Parts:
+    Placeholder(Arg(0), [SplitIndex("/", 2), SplitIndex(".", 0)]) -> input arg 0, split by "/" taking part 2, split by "." taking part 0: single value
+  Combination:
+    single argument
+
+"#;
+        assert_eq!(
+            expected,
+            explain_templates(&["{0/2.0}".to_string()], CombineMode::Cartesian).text
+        );
+    }
+}
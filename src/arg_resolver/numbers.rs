@@ -0,0 +1,183 @@
+use super::ResolveError;
+
+/// Arithmetic applied to integer value, e.g. :+1 or :*100
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Arithmetic {
+    Add(i64),
+    Sub(i64),
+    Mul(i64),
+    Div(i64),
+    Rem(i64),
+}
+
+impl Arithmetic {
+    //Division by zero is rejected here so template is invalid before any input is read
+    pub fn parse(operation: &str) -> Option<Arithmetic> {
+        let mut chars = operation.chars();
+        let operator = chars.next()?;
+        let operand: i64 = chars.as_str().parse().ok()?;
+        match operator {
+            '+' => Some(Arithmetic::Add(operand)),
+            '-' => Some(Arithmetic::Sub(operand)),
+            '*' => Some(Arithmetic::Mul(operand)),
+            '/' if operand != 0 => Some(Arithmetic::Div(operand)),
+            '%' if operand != 0 => Some(Arithmetic::Rem(operand)),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, value: &str) -> Result<String, ResolveError> {
+        let number: i64 = value
+            .trim()
+            .parse()
+            .map_err(|_| ResolveError::NotANumber(value.to_string()))?;
+        let result = match *self {
+            Arithmetic::Add(operand) => number.checked_add(operand),
+            Arithmetic::Sub(operand) => number.checked_sub(operand),
+            Arithmetic::Mul(operand) => number.checked_mul(operand),
+            Arithmetic::Div(operand) => number.checked_div(operand),
+            Arithmetic::Rem(operand) => number.checked_rem(operand),
+        };
+        result
+            .map(|r| r.to_string())
+            .ok_or_else(|| ResolveError::ArithmeticOverflow(value.to_string()))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Width and alignment of value, e.g. :05, :>10 or :*^8
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FormatSpec {
+    //:05 - number padded with zeros after its sign
+    ZeroPad(usize),
+    //:>10 - value aligned with fill character
+    Align(char, Align, usize),
+}
+
+impl FormatSpec {
+    pub fn parse(spec: &str) -> Option<FormatSpec> {
+        if let Some(width) = spec.strip_prefix('0') {
+            return width.parse().ok().map(FormatSpec::ZeroPad);
+        }
+        let align_at = spec.find(['<', '>', '^'])?;
+        let fill = match &spec[..align_at] {
+            "" => ' ',
+            fill => {
+                let mut chars = fill.chars();
+                let fill_char = chars.next()?;
+                if chars.next().is_some() {
+                    return None;
+                }
+                fill_char
+            }
+        };
+        let align = match &spec[align_at..align_at + 1] {
+            "<" => Align::Left,
+            ">" => Align::Right,
+            _ => Align::Center,
+        };
+        let width = spec[align_at + 1..].parse().ok()?;
+        Some(FormatSpec::Align(fill, align, width))
+    }
+
+    pub fn apply(&self, value: &str) -> Result<String, ResolveError> {
+        match *self {
+            FormatSpec::ZeroPad(width) => zero_pad(value, width),
+            FormatSpec::Align(fill, align, width) => Ok(align_value(value, fill, align, width)),
+        }
+    }
+}
+
+fn zero_pad(value: &str, width: usize) -> Result<String, ResolveError> {
+    let (sign, digits) = match value.strip_prefix(['-', '+']) {
+        Some(digits) => (&value[..1], digits),
+        None => ("", value),
+    };
+    if digits.parse::<f64>().is_err() || !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(ResolveError::NotANumber(value.to_string()));
+    }
+    let padding = width.saturating_sub(value.len());
+    Ok(format!("{}{}{}", sign, "0".repeat(padding), digits))
+}
+
+fn align_value(value: &str, fill: char, align: Align, width: usize) -> String {
+    let padding = width.saturating_sub(value.chars().count());
+    let (left, right) = match align {
+        Align::Left => (0, padding),
+        Align::Right => (padding, 0),
+        Align::Center => (padding / 2, padding - padding / 2),
+    };
+    let fill = fill.to_string();
+    format!("{}{}{}", fill.repeat(left), value, fill.repeat(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_apply_arithmetic() {
+        assert_eq!("42", Arithmetic::Add(1).apply("41").unwrap());
+        assert_eq!("-1", Arithmetic::Sub(2).apply("1").unwrap());
+        assert_eq!("300", Arithmetic::Mul(100).apply("3").unwrap());
+        assert_eq!("3", Arithmetic::Div(2).apply("7").unwrap());
+        assert_eq!("1", Arithmetic::Rem(2).apply("7").unwrap());
+    }
+
+    #[test]
+    fn should_fail_arithmetic_on_invalid_numbers() {
+        assert!(matches!(
+            Arithmetic::Add(1).apply("abc").unwrap_err(),
+            ResolveError::NotANumber(_)
+        ));
+        assert!(matches!(
+            Arithmetic::Mul(2).apply(&i64::MAX.to_string()).unwrap_err(),
+            ResolveError::ArithmeticOverflow(_)
+        ));
+        assert_eq!(None, Arithmetic::parse("/0"));
+    }
+
+    #[test]
+    fn should_parse_format_spec() {
+        assert_eq!(Some(FormatSpec::ZeroPad(5)), FormatSpec::parse("05"));
+        assert_eq!(
+            Some(FormatSpec::Align(' ', Align::Right, 10)),
+            FormatSpec::parse(">10")
+        );
+        assert_eq!(
+            Some(FormatSpec::Align('*', Align::Center, 8)),
+            FormatSpec::parse("*^8")
+        );
+    }
+
+    #[test]
+    fn should_format_values() {
+        assert_eq!("00042", FormatSpec::ZeroPad(5).apply("42").unwrap());
+        assert_eq!("-0042", FormatSpec::ZeroPad(5).apply("-42").unwrap());
+        assert_eq!("123456", FormatSpec::ZeroPad(5).apply("123456").unwrap());
+        assert_eq!(
+            "  ab",
+            FormatSpec::Align(' ', Align::Right, 4).apply("ab").unwrap()
+        );
+        assert_eq!(
+            "żb..",
+            FormatSpec::Align('.', Align::Left, 4).apply("żb").unwrap()
+        );
+        assert_eq!(
+            "-ab--",
+            FormatSpec::Align('-', Align::Center, 5)
+                .apply("ab")
+                .unwrap()
+        );
+        assert!(matches!(
+            FormatSpec::ZeroPad(5).apply("ab").unwrap_err(),
+            ResolveError::NotANumber(_)
+        ));
+    }
+}
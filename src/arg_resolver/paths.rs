@@ -0,0 +1,110 @@
+use std::path::{self, Component, Path, PathBuf};
+
+use super::ResolveError;
+
+/// Operation that treats value as filesystem path
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PathOperation<'a> {
+    //:stem - file name without last extension, archive.tar.gz -> archive.tar
+    Stem,
+    //:ext - last extension without dot, empty when there is none
+    Ext,
+    //:parent - parent directory, . when path has no directory
+    Parent,
+    //:name - file name with extension
+    Name,
+    //:abs - absolute path, symlinks are not resolved
+    Abs,
+    //:rel:BASE - path relative to BASE directory
+    Rel(&'a str),
+}
+
+impl PathOperation<'_> {
+    pub fn from_name(name: &str) -> Option<PathOperation<'static>> {
+        match name {
+            "stem" => Some(PathOperation::Stem),
+            "ext" => Some(PathOperation::Ext),
+            "parent" => Some(PathOperation::Parent),
+            "name" => Some(PathOperation::Name),
+            "abs" => Some(PathOperation::Abs),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, value: &str) -> Result<String, ResolveError> {
+        let path = Path::new(value);
+        let resolved = match self {
+            PathOperation::Stem => path.file_stem().map(PathBuf::from).unwrap_or_default(),
+            PathOperation::Ext => path.extension().map(PathBuf::from).unwrap_or_default(),
+            PathOperation::Parent => match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                Some(_) => PathBuf::from("."),
+                None => path.to_path_buf(),
+            },
+            PathOperation::Name => path.file_name().map(PathBuf::from).unwrap_or_default(),
+            PathOperation::Abs => absolute(path)?,
+            PathOperation::Rel(base) => relative_to(&absolute(path)?, &absolute(Path::new(base))?),
+        };
+        Ok(resolved.to_string_lossy().into_owned())
+    }
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, ResolveError> {
+    path::absolute(path).map_err(|error| ResolveError::InvalidPath(path.to_path_buf(), error))
+}
+
+//Both paths must be absolute, .. components are kept as they are
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let mut path_components = path.components().peekable();
+    let mut base_components = base.components().peekable();
+    while let (Some(p), Some(b)) = (path_components.peek(), base_components.peek()) {
+        if p != b {
+            break;
+        }
+        path_components.next();
+        base_components.next();
+    }
+    let relative: PathBuf = base_components
+        .map(|_| Component::ParentDir)
+        .chain(path_components)
+        .collect();
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_resolve_file_name_parts() {
+        let path = "dir.d/archive.tar.gz";
+        assert_eq!("archive.tar", PathOperation::Stem.apply(path).unwrap());
+        assert_eq!("gz", PathOperation::Ext.apply(path).unwrap());
+        assert_eq!("dir.d", PathOperation::Parent.apply(path).unwrap());
+        assert_eq!("archive.tar.gz", PathOperation::Name.apply(path).unwrap());
+    }
+
+    #[test]
+    fn should_resolve_missing_path_parts() {
+        assert_eq!("", PathOperation::Ext.apply("dir.d/Makefile").unwrap());
+        assert_eq!(".", PathOperation::Parent.apply("file.txt").unwrap());
+        assert_eq!(".bashrc", PathOperation::Stem.apply(".bashrc").unwrap());
+    }
+
+    #[test]
+    fn should_resolve_relative_path() {
+        assert_eq!(
+            "c/d.txt",
+            PathOperation::Rel("/a/b").apply("/a/b/c/d.txt").unwrap()
+        );
+        assert_eq!(
+            "../x/y.txt",
+            PathOperation::Rel("/a/b").apply("/a/x/y.txt").unwrap()
+        );
+        assert_eq!(".", PathOperation::Rel("/a/b/").apply("/a/b").unwrap());
+    }
+}
@@ -0,0 +1,127 @@
+use regex::Regex;
+
+use super::tokens::LexingError;
+
+/// Regex replacement of all matches in value, e.g. /s/IMG_([0-9]+)/photo-$1/
+#[derive(Debug)]
+pub struct Substitution {
+    regex: Regex,
+    replacement: String,
+}
+
+impl Substitution {
+    //Slash is escaped in template as \/ because it ends pattern and replacement
+    pub fn new(pattern: &str, replacement: &str) -> Result<Substitution, LexingError> {
+        let regex = Regex::new(&pattern.replace(r"\/", "/"))
+            .map_err(|error| LexingError::Regex(error.to_string()))?;
+        let replacement = replacement.replace(r"\/", "/");
+        check_group_references(&regex, &replacement)?;
+        Ok(Substitution { regex, replacement })
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    pub fn apply(&self, value: &str) -> String {
+        self.regex
+            .replace_all(value, self.replacement.as_str())
+            .into_owned()
+    }
+}
+
+//Regex replaces reference to unknown group with empty text, e.g. $1_x is read as group named 1_x,
+//so such references are rejected instead of silently dropping part of value
+fn check_group_references(regex: &Regex, replacement: &str) -> Result<(), LexingError> {
+    let mut rest = replacement;
+    while let Some(at) = rest.find('$') {
+        rest = &rest[at + 1..];
+        if let Some(after_dollar) = rest.strip_prefix('$') {
+            rest = after_dollar;
+            continue;
+        }
+        let (name, after_name) = match rest.strip_prefix('{').and_then(|r| r.split_once('}')) {
+            Some((name, after_name)) => (name, after_name),
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                rest.split_at(end)
+            }
+        };
+        if !name.is_empty() && !is_group(regex, name) {
+            return Err(LexingError::UnknownGroup(name.to_string()));
+        }
+        rest = after_name;
+    }
+    Ok(())
+}
+
+fn is_group(regex: &Regex, name: &str) -> bool {
+    match name.parse::<usize>() {
+        Ok(index) => index < regex.captures_len(),
+        Err(_) => regex.capture_names().flatten().any(|n| n == name),
+    }
+}
+
+impl PartialEq for Substitution {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str() && self.replacement == other.replacement
+    }
+}
+
+impl Eq for Substitution {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_replace_with_capture_groups() {
+        let substitution = Substitution::new(r"IMG_([0-9]+)\.JPG", "photo-$1.jpg").unwrap();
+        assert_eq!("photo-1234.jpg", substitution.apply("IMG_1234.JPG"));
+        assert_eq!("notes.txt", substitution.apply("notes.txt"));
+    }
+
+    #[test]
+    fn should_replace_escaped_slashes() {
+        let substitution = Substitution::new(r"\/", r"\/\/").unwrap();
+        assert_eq!("a//b//c", substitution.apply("a/b/c"));
+    }
+
+    #[test]
+    fn should_replace_with_groups_enclosed_in_braces() {
+        let substitution = Substitution::new("([a-z]+)([0-9]{2})", "${2}_${1}").unwrap();
+        assert_eq!("42_abc", substitution.apply("abc42"));
+        let substitution = Substitution::new("(?<name>[a-z]+)", "${name}_small$$").unwrap();
+        assert_eq!("abc_small$", substitution.apply("abc"));
+    }
+
+    #[test]
+    fn should_fail_on_unknown_group_in_replacement() {
+        assert_eq!(
+            LexingError::UnknownGroup("1_small".to_string()),
+            Substitution::new("([a-z]+)", "$1_small").unwrap_err()
+        );
+        assert_eq!(
+            LexingError::UnknownGroup("2".to_string()),
+            Substitution::new("([a-z]+)", "${2}").unwrap_err()
+        );
+        assert_eq!(
+            LexingError::UnknownGroup("id".to_string()),
+            Substitution::new("(?<name>[a-z]+)", "$id").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_fail_on_invalid_regex() {
+        assert!(matches!(
+            Substitution::new("(a", "b").unwrap_err(),
+            LexingError::Regex(_)
+        ));
+    }
+}
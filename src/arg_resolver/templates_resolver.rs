@@ -1,19 +1,26 @@
 use logos::Logos;
 
+use super::substitution;
 use super::tokens::{ArgPlaceholderToken, ArgPlaceholderToken::*, ArgTemplateToken, LexingError};
-use super::ArgTemplatePart;
 use super::ResolvedArgument;
+use super::TemplateError;
+use super::{ArgOperation, ArgSource, ArgTemplatePart};
 
 pub fn resolve_template_args(
     arg_templates: &[String],
-) -> Result<Vec<ResolvedArgument>, LexingError> {
+) -> Result<Vec<ResolvedArgument<'_>>, TemplateError> {
     arg_templates
         .iter()
-        .map(|a| resolve_arg_template(a.as_str()))
+        .map(|a| {
+            resolve_arg_template(a.as_str()).map_err(|error| TemplateError {
+                template: a.clone(),
+                error,
+            })
+        })
         .collect()
 }
 
-fn resolve_arg_template(arg_template: &str) -> Result<ResolvedArgument, LexingError> {
+pub fn resolve_arg_template(arg_template: &str) -> Result<ResolvedArgument<'_>, LexingError> {
     let mut lex = ArgTemplateToken::lexer(arg_template);
     let mut resolved = Vec::new();
     while let Some(token) = lex.next() {
@@ -35,39 +42,134 @@ fn resolve_arg_template(arg_template: &str) -> Result<ResolvedArgument, LexingEr
     Ok(resolved)
 }
 
-fn resolve_arg_placeholder(placeholder: &str) -> Result<ArgTemplatePart, LexingError> {
-    let lex = ArgPlaceholderToken::lexer(placeholder);
-    let tokens = lex.collect::<Result<Vec<ArgPlaceholderToken>, LexingError>>()?;
+fn resolve_arg_placeholder(placeholder: &str) -> Result<ArgTemplatePart<'_>, LexingError> {
+    let tokens = lex_placeholder(placeholder)?;
     match tokens.as_slice() {
-        [BraceOpen, Index(index), BraceClose] => Ok(ArgTemplatePart::Index(*index)),
-        [BraceOpen, Index(index), Separator(sep), BraceClose] => {
-            Ok(ArgTemplatePart::IndexSplit(*index, sep))
+        [BraceOpen, Index(index), content @ .., BraceClose] => {
+            resolve_placeholder_operations(ArgSource::Arg(*index), content)
         }
-        [BraceOpen, Index(index), Separator(sep), Index(index2), BraceClose] => {
-            Ok(ArgTemplatePart::IndexSplitIndex(*index, sep, *index2))
+        [BraceOpen, content @ .., BraceClose] => {
+            resolve_placeholder_operations(ArgSource::AllArgs, content)
         }
-        [BraceOpen, Separator(sep), Index(index), BraceClose] => {
-            Ok(ArgTemplatePart::SplitIndex(sep, *index))
+        //{#*100} would be split of entry number, which is always single integer
+        [EntryNumber, Separator(_), ..] => Err(LexingError::EntryNumberSplit),
+        [EntryNumber, content @ .., BraceClose] => {
+            resolve_placeholder_operations(ArgSource::EntryNumber, content)
+        }
+        [GroupKey, content @ .., BraceClose] => {
+            resolve_placeholder_operations(ArgSource::GroupKey, content)
+        }
+        [Line(index), content @ .., BraceClose] => {
+            resolve_placeholder_operations(ArgSource::Line(*index), content)
+        }
+        [Source((number, index)), content @ .., BraceClose] => {
+            resolve_placeholder_operations(ArgSource::Source(*number, *index), content)
         }
-        [BraceOpen, Separator(sep), BraceClose] => Ok(ArgTemplatePart::Split(sep)),
-        [BraceOpen, BraceClose] => Ok(ArgTemplatePart::Empty),
         _ => Err(LexingError::InvalidDefinition),
     }
 }
 
+//Adjacent unquoted separators are single separator, e.g. {0:x1} is lexed as ":" and "x"
+//but it splits by ":x" like it always did
+fn lex_placeholder(placeholder: &str) -> Result<Vec<ArgPlaceholderToken<'_>>, LexingError> {
+    let mut tokens = Vec::new();
+    let mut separator_start = None;
+    for (token, span) in ArgPlaceholderToken::lexer(placeholder).spanned() {
+        match (token?, separator_start) {
+            (Separator(sep), Some(start)) if sep.len() == span.len() => {
+                tokens.pop();
+                tokens.push(Separator(&placeholder[start..span.end]));
+            }
+            (Separator(sep), None) if sep.len() == span.len() => {
+                separator_start = Some(span.start);
+                tokens.push(Separator(sep));
+            }
+            (token, _) => {
+                separator_start = None;
+                tokens.push(token);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn resolve_placeholder_operations<'a>(
+    source: ArgSource,
+    tokens: &[ArgPlaceholderToken<'a>],
+) -> Result<ArgTemplatePart<'a>, LexingError> {
+    let mut rest = tokens;
+    let mut operations = Vec::new();
+    while !rest.is_empty() {
+        rest = match rest {
+            [Separator(sep), Index(index), rest @ ..] => {
+                operations.push(ArgOperation::SplitIndex(sep, *index));
+                rest
+            }
+            [Separator(sep), rest @ ..] => {
+                operations.push(ArgOperation::Split(sep));
+                rest
+            }
+            [Path(operation), rest @ ..] => {
+                operations.push(ArgOperation::Path(operation.clone()));
+                rest
+            }
+            [Substitution((pattern, replacement)), rest @ ..] => {
+                let substitution = substitution::Substitution::new(pattern, replacement)?;
+                operations.push(ArgOperation::Substitute(substitution));
+                rest
+            }
+            [Slice((start, end)), rest @ ..] => {
+                operations.push(ArgOperation::Slice(*start, *end));
+                rest
+            }
+            [Arithmetic(arithmetic), rest @ ..] => {
+                operations.push(ArgOperation::Arithmetic(*arithmetic));
+                rest
+            }
+            [Format(spec), rest @ ..] => {
+                operations.push(ArgOperation::Format(*spec));
+                rest
+            }
+            [Join(sep)] => {
+                operations.push(ArgOperation::Join(sep));
+                &[]
+            }
+            _ => return Err(LexingError::InvalidDefinition),
+        };
+    }
+    Ok(ArgTemplatePart::Placeholder(source, operations))
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::numbers::{Arithmetic, FormatSpec};
+    use super::super::paths::PathOperation;
     use super::*;
 
+    #[test]
+    fn should_reject_split_of_entry_number() {
+        assert_eq!(
+            Err(LexingError::EntryNumberSplit),
+            resolve_arg_template("{#*100}")
+        );
+        assert_eq!(
+            Err(LexingError::EntryNumberSplit),
+            resolve_arg_template("x{#.0}")
+        );
+    }
+
     #[test]
     fn should_parse_arg_template() {
         assert_eq!(
             vec![
                 ArgTemplatePart::FreeText("freeText"),
-                ArgTemplatePart::IndexSplitIndex(0, ".", 0),
+                ArgTemplatePart::Placeholder(
+                    ArgSource::Arg(0),
+                    vec![ArgOperation::SplitIndex(".", 0)]
+                ),
                 ArgTemplatePart::FreeText("-"),
-                ArgTemplatePart::Split("."),
-                ArgTemplatePart::Empty,
+                ArgTemplatePart::Placeholder(ArgSource::AllArgs, vec![ArgOperation::Split(".")]),
+                ArgTemplatePart::Placeholder(ArgSource::AllArgs, vec![]),
                 ArgTemplatePart::FreeText("text"),
                 ArgTemplatePart::FreeText("{EscapedText}"),
                 ArgTemplatePart::FreeText("{{}}"),
@@ -92,29 +194,140 @@ mod tests {
     #[test]
     fn should_parse_arg_placeholders() {
         assert_eq!(
-            ArgTemplatePart::Index(0),
+            ArgTemplatePart::Placeholder(ArgSource::Arg(0), vec![]),
             resolve_arg_placeholder("{0}").unwrap()
         );
         assert_eq!(
-            ArgTemplatePart::IndexSplit(0, "."),
+            ArgTemplatePart::Placeholder(ArgSource::Arg(0), vec![ArgOperation::Split(".")]),
             resolve_arg_placeholder("{0.}").unwrap()
         );
         assert_eq!(
-            ArgTemplatePart::IndexSplitIndex(0, ".", 1),
+            ArgTemplatePart::Placeholder(ArgSource::Arg(0), vec![ArgOperation::SplitIndex(".", 1)]),
             resolve_arg_placeholder("{0.1}").unwrap()
         );
         assert_eq!(
-            ArgTemplatePart::SplitIndex(".", 0),
+            ArgTemplatePart::Placeholder(
+                ArgSource::AllArgs,
+                vec![ArgOperation::SplitIndex(".", 0)]
+            ),
             resolve_arg_placeholder("{.0}").unwrap()
         );
         assert_eq!(
-            ArgTemplatePart::Split("."),
+            ArgTemplatePart::Placeholder(ArgSource::AllArgs, vec![ArgOperation::Split(".")]),
             resolve_arg_placeholder("{.}").unwrap()
         );
         assert_eq!(
-            ArgTemplatePart::Empty,
+            ArgTemplatePart::Placeholder(ArgSource::AllArgs, vec![]),
             resolve_arg_placeholder("{}").unwrap()
         );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(
+                ArgSource::Arg(0),
+                vec![ArgOperation::SplitIndex("1x", 1)]
+            ),
+            resolve_arg_placeholder("{0'1x'1}").unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(ArgSource::AllArgs, vec![ArgOperation::Split("}")]),
+            resolve_arg_placeholder(r#"{"}"}"#).unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(
+                ArgSource::AllArgs,
+                vec![ArgOperation::SplitIndex(".", 0), ArgOperation::Join(",")]
+            ),
+            resolve_arg_placeholder("{.0|join:,}").unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(
+                ArgSource::Arg(0),
+                vec![
+                    ArgOperation::Path(PathOperation::Parent),
+                    ArgOperation::SplitIndex("-", 1)
+                ]
+            ),
+            resolve_arg_placeholder("{0:parent-1}").unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(
+                ArgSource::EntryNumber,
+                vec![
+                    ArgOperation::Arithmetic(Arithmetic::Add(10)),
+                    ArgOperation::Format(FormatSpec::ZeroPad(4))
+                ]
+            ),
+            resolve_arg_placeholder("{#:+10:04}").unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(ArgSource::AllArgs, vec![ArgOperation::Join(" ")]),
+            resolve_arg_placeholder("{|join: }").unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(
+                ArgSource::Arg(0),
+                vec![
+                    ArgOperation::SplitIndex("/", 2),
+                    ArgOperation::SplitIndex(".", 0)
+                ]
+            ),
+            resolve_arg_placeholder("{0/2.0}").unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(
+                ArgSource::AllArgs,
+                vec![
+                    ArgOperation::Split("/"),
+                    ArgOperation::SplitIndex(".", 1),
+                    ArgOperation::Split("_")
+                ]
+            ),
+            resolve_arg_placeholder("{'/'.1_}").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_parse_separators_with_colon_as_before_placeholder_operations() {
+        let split_index = |sep, index| {
+            ArgTemplatePart::Placeholder(
+                ArgSource::Arg(0),
+                vec![ArgOperation::SplitIndex(sep, index)],
+            )
+        };
+        assert_eq!(
+            split_index(":x", 1),
+            resolve_arg_placeholder("{0:x1}").unwrap()
+        );
+        assert_eq!(
+            split_index("x:y", 1),
+            resolve_arg_placeholder("{0x:y1}").unwrap()
+        );
+        assert_eq!(
+            split_index("::", 1),
+            resolve_arg_placeholder("{0::1}").unwrap()
+        );
+        assert_eq!(
+            split_index(":", 5),
+            resolve_arg_placeholder("{0:5}").unwrap()
+        );
+        assert_eq!(
+            split_index("+", 1),
+            resolve_arg_placeholder("{0+1}").unwrap()
+        );
+        assert_eq!(
+            split_index(":-", 1),
+            resolve_arg_placeholder("{0':-'1}").unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(ArgSource::Arg(0), vec![ArgOperation::Split(":stemx")]),
+            resolve_arg_placeholder("{0':stemx'}").unwrap()
+        );
+        assert_eq!(
+            ArgTemplatePart::Placeholder(
+                ArgSource::Arg(0),
+                vec![ArgOperation::Split("'"), ArgOperation::Split(":")]
+            ),
+            resolve_arg_placeholder("{0\"'\":}").unwrap()
+        );
     }
 
     #[test]
@@ -159,5 +372,13 @@ mod tests {
             LexingError::InvalidDefinition,
             resolve_arg_placeholder("{{0}}").unwrap_err()
         );
+        assert_eq!(
+            LexingError::InvalidDefinition,
+            resolve_arg_placeholder("{|join:,}x").unwrap_err()
+        );
+        assert_eq!(
+            LexingError::InvalidDefinition,
+            resolve_arg_placeholder("{0''}").unwrap_err()
+        );
     }
 }
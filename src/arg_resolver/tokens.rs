@@ -1,11 +1,16 @@
 use std::num::ParseIntError;
 
 use logos::Logos;
+use thiserror::Error;
+
+use super::numbers::{Arithmetic, FormatSpec};
+use super::paths::PathOperation;
 
 #[derive(Debug, Logos, PartialEq)]
 #[logos(error = LexingError)]
 pub enum ArgTemplateToken {
-    #[regex(r"\{[^{}]*\}", priority = 2)]
+    //Quoted parts of placeholder may contain braces, substitution may contain escaped or balanced ones
+    #[regex(r#"\{([^{}'"\\]|\\.|'[^']*'|"[^"]*"|\{[^{}'"\\]*\})*\}"#, priority = 2)]
     ArgPlaceholder,
     #[regex(r"[^{}\\]+", priority = 0)]
     FreeText,
@@ -18,19 +23,109 @@ pub enum ArgTemplateToken {
 pub enum ArgPlaceholderToken<'a> {
     #[token("{")]
     BraceOpen,
+    //Entry number is only allowed at the beginning, elsewhere # is regular separator
+    #[token("{#")]
+    EntryNumber,
+    #[token("{key")]
+    GroupKey,
+    #[regex(r"\{L[0-9]+", |lex| lex.slice()[2..].parse())]
+    Line(usize),
+    #[regex(r"\{S[0-9]+(:[0-9]+)?", |lex| parse_source(lex.slice()))]
+    Source((usize, Option<usize>)),
     #[token("}")]
     BraceClose,
     #[regex(r"[0-9]+", |lex| lex.slice().parse())]
     Index(usize),
-    #[regex(r"[^0-9\{}]+", |lex| lex.slice())]
+    //Colon is separator only on its own so it does not swallow path operations
+    #[regex(r#"[^0-9\{}'":]+"#, |lex| lex.slice())]
+    #[regex(r":+", |lex| lex.slice())]
+    #[regex(r"'[^']*'", |lex| unquote(lex.slice()))]
+    #[regex(r#""[^"]*""#, |lex| unquote(lex.slice()))]
     Separator(&'a str),
+    //Only known names, other text after colon is part of separator, e.g. {0:x1}
+    #[regex(r":(stem|ext|parent|name|abs)", |lex| PathOperation::from_name(&lex.slice()[1..]))]
+    #[regex(r#":rel:[^{}'"|:]+"#, |lex| PathOperation::Rel(&lex.slice()[5..]))]
+    #[regex(r":rel:'[^']*'", |lex| unquote(&lex.slice()[5..]).map(PathOperation::Rel))]
+    #[regex(r#":rel:"[^"]*""#, |lex| unquote(&lex.slice()[5..]).map(PathOperation::Rel))]
+    Path(PathOperation<'a>),
+    //Pattern and replacement are compiled when template is resolved
+    #[regex(r"/s/([^/{}\\]|\\.|\{[^/{}\\]*\})*/([^/{}\\]|\\.|\{[^/{}\\]*\})*/", |lex| split_substitution(lex.slice()), priority = 10)]
+    Substitution((&'a str, &'a str)),
+    #[regex(r"\[-?[0-9]*:-?[0-9]*\]", |lex| parse_slice(lex.slice()))]
+    Slice((Option<isize>, Option<isize>)),
+    #[regex(r":[+\-*/%][0-9]+", |lex| Arithmetic::parse(&lex.slice()[1..]))]
+    Arithmetic(Arithmetic),
+    //Zero padding must start with 0 so :5 still means split by colon and take 5th part
+    #[regex(r":0[0-9]+", |lex| FormatSpec::parse(&lex.slice()[1..]))]
+    #[regex(r#":[^{}'"]?[<>^][0-9]+"#, |lex| FormatSpec::parse(&lex.slice()[1..]))]
+    Format(FormatSpec),
+    #[regex(r#"\|join:[^{}'"]*"#, |lex| &lex.slice()[6..], priority = 10)]
+    #[regex(r"\|join:'[^']*'", |lex| &lex.slice()[7..lex.slice().len() - 1])]
+    #[regex(r#"\|join:"[^"]*""#, |lex| &lex.slice()[7..lex.slice().len() - 1])]
+    Join(&'a str),
+}
+
+//Quotes allow separator to contain digits and braces, empty separator is not allowed
+fn unquote(quoted: &str) -> Option<&str> {
+    Some(&quoted[1..quoted.len() - 1]).filter(|s| !s.is_empty())
+}
+
+///s/pattern/replacement/ where / may be escaped in both parts and braces may enclose
+//repetition like {2,3} or group reference like ${1}
+fn split_substitution(substitution: &str) -> Option<(&str, &str)> {
+    let content = &substitution[3..substitution.len() - 1];
+    let mut escaped = false;
+    let separator = content.char_indices().find_map(|(i, c)| match c {
+        '\\' if !escaped => {
+            escaped = true;
+            None
+        }
+        '/' if !escaped => Some(i),
+        _ => {
+            escaped = false;
+            None
+        }
+    })?;
+    Some((&content[..separator], &content[separator + 1..]))
+}
+
+//{S1:0} where source is numbered from 1 and arg index is optional
+fn parse_source(source: &str) -> Option<(usize, Option<usize>)> {
+    let (number, index) = match source[2..].split_once(':') {
+        Some((number, index)) => (number, Some(index.parse().ok()?)),
+        None => (&source[2..], None),
+    };
+    let number = number.parse().ok().filter(|number| *number > 0)?;
+    Some((number, index))
+}
+
+//[start:end] where both positions are optional
+fn parse_slice(slice: &str) -> Option<(Option<isize>, Option<isize>)> {
+    let (start, end) = slice[1..slice.len() - 1].split_once(':')?;
+    let position = |pos: &str| match pos {
+        "" => Some(None),
+        pos => pos.parse().ok().map(Some),
+    };
+    Some((position(start)?, position(end)?))
 }
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Error, Default, Debug, Clone, PartialEq)]
 pub enum LexingError {
+    #[error("invalid integer, {0}")]
     InvalidInteger(String),
     #[default]
+    #[error("invalid placeholder definition")]
     InvalidDefinition,
+    #[error("invalid regex, {0}")]
+    Regex(String),
+    #[error("unknown capture group '{0}' in replacement, group followed by text may be enclosed in braces e.g. ${{1}}_text")]
+    UnknownGroup(String),
+    #[error("template resolves to many values while single one is expected, values may be joined e.g. {{|join:_}}")]
+    NotSingleValue,
+    #[error("group key is available only when entries are grouped with --group-by")]
+    NoGroupKey,
+    #[error("entry number cannot be split, operations on it follow colon, e.g. {{#:*100}}")]
+    EntryNumberSplit,
 }
 
 impl From<ParseIntError> for LexingError {
@@ -111,6 +206,241 @@ mod tests {
         assert_eq!(lex.next(), None);
     }
 
+    #[test]
+    fn lexer_should_parse_arg_placeholder_with_join() {
+        let mut lex = ArgPlaceholderToken::lexer("{.0|join:,}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Separator("."))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Join(","))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer("{|join:1 2}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Join("1 2"))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn lexer_should_parse_arg_placeholder_with_quoted_separator() {
+        let mut lex = ArgPlaceholderToken::lexer("{0'1x'1}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Separator("1x"))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(1))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer(r#"{"}'"|join:"'"}"#);
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Separator("}'"))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Join("'"))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn lexer_should_parse_arg_placeholder_with_path_operations() {
+        let mut lex = ArgPlaceholderToken::lexer("{0:parent:name}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Path(PathOperation::Parent)))
+        );
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Path(PathOperation::Name)))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer("{:rel:/tmp/a1|join:,}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Path(PathOperation::Rel("/tmp/a1"))))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Join(","))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn lexer_should_parse_colon_as_separator() {
+        let mut lex = ArgPlaceholderToken::lexer("{0:1}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Separator(":"))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(1))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer("{0:unknown}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Separator(":"))));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Separator("unknown")))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+    }
+
+    #[test]
+    fn lexer_should_parse_entry_number_with_arithmetic_and_format() {
+        let mut lex = ArgPlaceholderToken::lexer("{#:*100:>6}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::EntryNumber)));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Arithmetic(Arithmetic::Mul(100))))
+        );
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Format(
+                FormatSpec::parse(">6").unwrap()
+            )))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer("{0#05}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Separator("#"))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(5))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn lexer_should_parse_arg_placeholder_with_slice() {
+        let mut lex = ArgPlaceholderToken::lexer("{0[-4:]}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Slice((Some(-4), None))))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer("{[0:3]}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Slice((Some(0), Some(3)))))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn lexer_should_parse_arg_placeholder_with_substitution() {
+        let mut lex = ArgPlaceholderToken::lexer(r"{0/s/IMG_([0-9]+)\.JPG/photo-$1.jpg/}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Substitution((
+                r"IMG_([0-9]+)\.JPG",
+                "photo-$1.jpg"
+            ))))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer(r"{/s/a\/b/c/}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Substitution((r"a\/b", "c"))))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer(r"{/s/([0-9]{2})\{/${1}_small/}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Substitution((
+                r"([0-9]{2})\{",
+                "${1}_small"
+            ))))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn lexer_should_parse_line_placeholder() {
+        let mut lex = ArgPlaceholderToken::lexer("{L1.0}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Line(1))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Separator("."))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer("{Lx}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Separator("Lx"))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+    }
+
+    #[test]
+    fn lexer_should_parse_source_placeholder() {
+        let mut lex = ArgPlaceholderToken::lexer("{S2:1:stem}");
+
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Source((2, Some(1)))))
+        );
+        assert_eq!(
+            lex.next(),
+            Some(Ok(ArgPlaceholderToken::Path(PathOperation::Stem)))
+        );
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+        assert_eq!(lex.next(), None);
+
+        let mut lex = ArgPlaceholderToken::lexer("{S1}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Source((1, None)))));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceClose)));
+
+        let mut lex = ArgPlaceholderToken::lexer("{S0}");
+
+        assert_eq!(lex.next(), Some(Err(LexingError::InvalidDefinition)));
+    }
+
+    #[test]
+    fn lexer_should_fail_to_parse_empty_quoted_separator() {
+        let mut lex = ArgPlaceholderToken::lexer("{0''}");
+
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::BraceOpen)));
+        assert_eq!(lex.next(), Some(Ok(ArgPlaceholderToken::Index(0))));
+        assert_eq!(lex.next(), Some(Err(LexingError::InvalidDefinition)));
+    }
+
     use super::ArgTemplateToken;
 
     #[test]
@@ -133,6 +463,18 @@ mod tests {
         assert_eq!(lex.next(), None);
     }
 
+    #[test]
+    fn lexer_should_parse_arg_template_with_quoted_braces_in_placeholder() {
+        let mut lex = ArgTemplateToken::lexer(r#"{0'}'1}x{"{"}"#);
+        assert_eq!(lex.next(), Some(Ok(ArgTemplateToken::ArgPlaceholder)));
+        assert_eq!(lex.slice(), "{0'}'1}");
+        assert_eq!(lex.next(), Some(Ok(ArgTemplateToken::FreeText)));
+        assert_eq!(lex.slice(), "x");
+        assert_eq!(lex.next(), Some(Ok(ArgTemplateToken::ArgPlaceholder)));
+        assert_eq!(lex.slice(), r#"{"{"}"#);
+        assert_eq!(lex.next(), None);
+    }
+
     #[test]
     fn lexer_should_parse_arg_template_with_free_text() {
         let mut lex = ArgTemplateToken::lexer("free.0{0}1_text");
@@ -181,12 +523,15 @@ mod tests {
         assert_eq!(lex.slice(), "}");
         assert_eq!(lex.next(), None);
 
-        let mut lex = ArgTemplateToken::lexer("{{0}}");
-        assert_eq!(lex.next(), Some(Err(LexingError::InvalidDefinition)));
-        assert_eq!(lex.slice(), "{");
+        let mut lex = ArgTemplateToken::lexer("{0}}");
         assert_eq!(lex.next(), Some(Ok(ArgTemplateToken::ArgPlaceholder)));
         assert_eq!(lex.next(), Some(Err(LexingError::InvalidDefinition)));
         assert_eq!(lex.slice(), "}");
+
+        //Nested braces are read as one placeholder, which is rejected when it is resolved
+        let mut lex = ArgTemplateToken::lexer("{{0}}");
+        assert_eq!(lex.next(), Some(Ok(ArgTemplateToken::ArgPlaceholder)));
+        assert_eq!(lex.next(), None);
     }
 
     #[test]
@@ -1,6 +1,8 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
-use clap::{arg, command, Args};
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, ValueEnum};
 
 use clap::Parser;
 
@@ -18,19 +20,141 @@ pub struct Cli {
     /// - {x} - x-th argument
     /// - {x<sep>} - x-th argument splittded by <sep> and all parts of split are taken
     /// - {x<sep>y} - x-th argument splittded by <sep> and y-th part of split is taken.
+    /// - {...|join:<sep>} - any of above with all its values joined by <sep> into single argument
+    /// - {x:name}, {x:stem}, {x:ext}, {x:parent}, {x:abs}, {x:rel:<base>} - x-th argument treated as path
+    /// - {x:05}, {x:>10}, {x:+1}, {x:*100} - x-th argument formatted or computed as number
+    /// - {x[start:end]} - characters of x-th argument, e.g. {0[0:3]} or {0[-4:]}
+    /// - {x/s/<pattern>/<replacement>/} - x-th argument with regex matches replaced, e.g. {0/s/IMG_([0-9]+)/photo-$1/}
+    /// - {#} - entry number, e.g. {#:-1:*100}
+    /// - {L0} - whole first line of entry made with --lines, e.g. {L1.0}
+    /// - {S1}, {S1:0} - all args or single arg of first of input sources combined with --values and --args-file
+    ///
+    /// Split steps may be chained, e.g. {0/2.0} splits by / takes 3rd part then splits it by . and takes 1st part
+    /// Separator containing digits or braces must be quoted, e.g. {0'1x'1} or {0"}"}, as well as separator
+    /// that reads as operation, e.g. {0':-'1} splits by :- while {0:-1} subtracts 1
     #[arg(verbatim_doc_comment, allow_hyphen_values = true)]
     pub args_templates: Vec<String>,
+    /// How array placeholders in single template are combined: cartesian product of all values
+    /// or element-wise pairing, which requires arrays of equal lengths
+    #[arg(long, value_enum, default_value_t = CombineMode::Cartesian)]
+    pub combine: CombineMode,
     /// Separator between args. Each entry line will be splitted by this separator
     #[arg(short, long, default_value = " ")]
     pub args_separator: String,
     /// Print command with resolved args instead of running it
     #[arg(short, long)]
     pub debug: bool,
-    /// Reads arguments from file instead of standard input
+    /// How printed commands are formatted, args are always quoted so output can be pasted into shell
+    #[arg(long, value_enum, default_value_t = DebugFormat::Shell)]
+    pub debug_format: DebugFormat,
+    /// Print execution plan of all entries in given format instead of running commands
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with = "debug")]
+    pub plan: Option<PlanFormat>,
+    /// Explains how given templates are parsed and combined into arguments, nothing is run. Fails when any template is invalid
+    #[arg(long, value_name = "TEMPLATE", num_args = 1.., allow_hyphen_values = true)]
+    pub explain: Vec<String>,
+    /// Only validates templates without reading input or running anything.
+    /// When SAMPLE entry is given it is resolved and command is printed
+    #[arg(long, value_name = "SAMPLE", num_args = 0..=1, require_equals = true)]
+    pub check: Option<Option<String>>,
+    /// Runs only entries matching expression: <template><operator><value>, e.g. {1}~^prod, {2}>=100 or #args==3.
+    /// Operators: ~ and !~ (regex), ==, !=, <, <=, >, >= (numbers). May be repeated, all must match
+    #[arg(long, value_name = "EXPR", allow_hyphen_values = true)]
+    pub filter: Vec<String>,
+    /// Skips entries matching expression, same as in --filter. May be repeated, any match skips entry
+    #[arg(long, value_name = "EXPR", allow_hyphen_values = true)]
+    pub exclude: Vec<String>,
+    /// Runs only first of entries with the same KEY template, e.g. --unique={1}. Whole entries are compared by default
+    #[arg(long, value_name = "KEY", num_args = 0..=1, require_equals = true)]
+    pub unique: Option<Option<String>>,
+    /// Runs entries sorted by KEY template, e.g. --sort={1}. Keys are compared as numbers when all of them are numbers
+    #[arg(long, value_name = "KEY", num_args = 0..=1, require_equals = true)]
+    pub sort: Option<Option<String>>,
+    /// Runs only N randomly chosen entries, they are run in the same order as given
+    #[arg(long, value_name = "N")]
+    pub sample: Option<usize>,
+    /// Seed of random sample so the same entries are chosen in each run
+    #[arg(long, requires = "sample")]
+    pub seed: Option<u64>,
+    /// Runs command once per group of entries with the same KEY template, e.g. {0.0}.
+    /// Key is available as {key} and {x} resolves to array with x-th arg of each group member
+    #[arg(long, value_name = "KEY")]
+    pub group_by: Option<String>,
+    /// Ask on terminal before running each command: y(es), n(o), a(ll remaining), q(uit)
+    #[arg(short = 'p', long, conflicts_with_all = ["debug", "plan"])]
+    pub interactive: bool,
+    /// Reads arguments from file instead of standard input. May be repeated, see --values
     #[arg(short = 'f', long, value_name = "FILE")]
-    pub args_file: Option<PathBuf>,
+    pub args_file: Vec<PathBuf>,
+    /// Comma separated args used instead of standard input, each is separate line, e.g. --values 1,2,3.
+    /// May be repeated, then entries of all sources (files first) are combined into one entry each with each
+    /// and args of n-th source are available as {Sn} or {Sn:0}, {Sn:1}, ...
+    #[arg(long, value_name = "VALUES")]
+    pub values: Vec<String>,
+    /// Combines entries of many sources by their position instead of each with each, shorter sources are repeated
+    #[arg(long)]
+    pub link: bool,
+    /// Writes stdout of each command to file. Path is an argument template resolved for each entry, e.g. out/{0.0}.log
+    #[arg(long, value_name = "TEMPLATE")]
+    pub stdout_to: Option<String>,
+    /// Writes stderr of each command to file. Path is an argument template resolved for each entry, e.g. err/{0.0}.log
+    #[arg(long, value_name = "TEMPLATE")]
+    pub stderr_to: Option<String>,
+    /// Writes stdout, stderr, exit code, command line and timing of each command into separate directory per entry
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["stdout_to", "stderr_to"])]
+    pub results: Option<PathBuf>,
+    /// Appends record of each executed command (entry number, start time, duration, exit code and command) to file
+    #[arg(long, value_name = "FILE")]
+    pub joblog: Option<PathBuf>,
+    /// Skips entries which command already completed successfully according to job log
+    #[arg(long, requires = "joblog")]
+    pub resume: bool,
+    /// Terminates command with SIGTERM when it runs longer than given duration, e.g. 500ms, 30s, 5m, 1h
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub timeout: Option<Duration>,
+    /// Time given to command to exit after it was terminated because of timeout or interruption, then it is killed with SIGKILL
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "5s")]
+    pub kill_after: Duration,
+    /// Number of times failed command is retried before it is counted as failed
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub retries: usize,
+    /// Delay before first retry, each next retry waits twice as long as previous one
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "1s", requires = "retries")]
+    pub retry_delay: Duration,
+    /// Retries command only when it exits with one of these codes, by default any failure is retried
+    #[arg(
+        long,
+        value_name = "CODES",
+        value_delimiter = ',',
+        requires = "retries"
+    )]
+    pub retry_on: Vec<i32>,
     #[command(flatten)]
     pub entries: EntriesOptions,
+    #[command(flatten)]
+    pub window: WindowOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CombineMode {
+    /// Each value of array is combined with each value of other arrays
+    Cartesian,
+    /// Values of arrays are paired by their position
+    Zip,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DebugFormat {
+    /// Whole command in single line
+    Shell,
+    /// Each argument in separate line
+    Lines,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum PlanFormat {
+    /// JSON object per entry in separate line
+    Json,
 }
 
 #[derive(Args)]
@@ -47,4 +171,94 @@ pub struct EntriesOptions {
     /// Splits input into entries of specified size
     #[arg(short = 's', long = "entries-size", default_value_t = 0)]
     pub entry_size: usize,
+
+    /// Groups every N whole lines into one entry, each line is available as {L0}, {L1}, ...
+    #[arg(short = 'L', long = "lines", value_name = "N", default_value_t = 0)]
+    pub lines: usize,
+}
+
+#[derive(Args)]
+pub struct WindowOptions {
+    /// Splits input into overlapping entries of N args, each next entry starts --step args later
+    #[arg(
+        long = "window",
+        value_name = "N",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        conflicts_with_all = ["single_entry", "entry_size", "entries_separator", "lines"]
+    )]
+    pub size: Option<usize>,
+
+    /// Number of args (or lines) between starts of consecutive windows
+    #[arg(
+        long,
+        value_name = "M",
+        default_value_t = 1,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+        requires = "size"
+    )]
+    pub step: usize,
+
+    /// Whether windows are made of args or whole lines
+    #[arg(long, value_enum, default_value_t = WindowUnit::Args, requires = "size")]
+    pub window_by: WindowUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WindowUnit {
+    /// Args of all lines split by args separator
+    Args,
+    /// Whole lines, args of window lines are joined with args separator
+    Lines,
+}
+
+/// Parses duration with unit suffix (ms, s, m, h), number without suffix is treated as seconds
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split_at = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split_at);
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}'", value))?;
+    let seconds = match unit {
+        "ms" => amount / 1000.0,
+        "" | "s" => amount,
+        "m" => amount * 60.0,
+        "h" => amount * 3600.0,
+        _ => return Err(format!("Unknown duration unit '{}'", unit)),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("Duration '{}' is too long", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn should_parse_duration_with_units() {
+        assert_eq!(Ok(Duration::from_millis(500)), parse_duration("500ms"));
+        assert_eq!(Ok(Duration::from_millis(1500)), parse_duration("1.5s"));
+        assert_eq!(Ok(Duration::from_secs(10)), parse_duration("10"));
+        assert_eq!(Ok(Duration::from_secs(120)), parse_duration("2m"));
+        assert_eq!(Ok(Duration::from_secs(3600)), parse_duration("1h"));
+    }
+
+    #[test]
+    fn should_fail_to_parse_invalid_duration() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("10d").is_err());
+        assert!(parse_duration("1.2.3s").is_err());
+    }
+
+    #[test]
+    fn should_reject_window_with_other_entries_options() {
+        for option in ["--lines", "--entries-size"] {
+            let parsed = Cli::try_parse_from(["axe", "--window", "2", option, "2", "echo"]);
+            assert!(parsed.is_err());
+        }
+    }
 }
@@ -1,23 +1,359 @@
-use std::process::Command;
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io, iter,
+    os::unix::process::ExitStatusExt,
+    path::Path,
+    process::{Child, Command, ExitStatus},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
 
-use crate::cli::Cli;
+use crate::{
+    arg_resolver::ResolvedEntry,
+    cli::{Cli, DebugFormat},
+    interactive::{self, Answer},
+    joblog::{self, JobLog},
+    plan, results, shell,
+    signals::Signals,
+    summary::RunSummary,
+};
 
-pub fn execute_cmd(cli: &Cli, cmd_args: Vec<String>) {
-    if cli.debug {
-        println!("{} {}", cli.cmd, cmd_args.join(" "));
-    } else {
-        run_command(cli, &cmd_args);
-    };
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Outcome of command that was run to completion
+pub struct Execution {
+    pub started_at: SystemTime,
+    pub duration: Duration,
+    pub status: ExitStatus,
+    pub termination: Option<Termination>,
+}
+
+/// Reason why axe had to terminate command before it finished on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    TimedOut,
+    Interrupted,
+}
+
+impl Execution {
+    /// Exit code of command, for commands terminated by signal it is 128 + signal number like in shells
+    pub fn exit_code(&self) -> i32 {
+        self.status
+            .code()
+            .or_else(|| self.status.signal().map(|signal| 128 + signal))
+            .unwrap_or(-1)
+    }
+}
+
+/// Runs commands for resolved entries, keeping state that spans whole run
+pub struct CmdExecutor<'a> {
+    cli: &'a Cli,
+    joblog: Option<JobLog>,
+    completed_cmdlines: HashSet<String>,
+    summary: RunSummary,
+    signals: Signals,
+    confirmed_all: bool,
+    //User quit at prompt, remaining entries are skipped
+    quit: bool,
+    //Confirmation could not be asked, remaining entries are not run
+    aborted: bool,
+}
+
+impl<'a> CmdExecutor<'a> {
+    pub fn new(cli: &'a Cli) -> io::Result<CmdExecutor<'a>> {
+        let completed_cmdlines = match (&cli.joblog, cli.resume) {
+            (Some(path), true) => joblog::read_completed_commands(path)?,
+            _ => HashSet::new(),
+        };
+        let joblog = cli.joblog.as_deref().map(JobLog::open).transpose()?;
+        Ok(CmdExecutor {
+            cli,
+            joblog,
+            completed_cmdlines,
+            summary: RunSummary::default(),
+            signals: Signals::register()?,
+            confirmed_all: false,
+            quit: false,
+            aborted: false,
+        })
+    }
+
+    pub fn summary(&self) -> &RunSummary {
+        &self.summary
+    }
+
+    /// Counts entries that were not executed because of --filter or --exclude
+    pub fn filtered_out(&mut self, count: usize) {
+        self.summary.filtered_out += count;
+    }
+
+    /// Signal that interrupted run, once received no more commands are executed
+    pub fn received_signal(&self) -> Option<i32> {
+        self.signals.received()
+    }
+
+    pub fn execute(&mut self, mut entry: ResolvedEntry) {
+        if self.aborted || self.signals.received().is_some() {
+            self.summary.not_run += 1;
+            return;
+        }
+        if self.quit {
+            self.summary.skipped += 1;
+            return;
+        }
+        if self
+            .completed_cmdlines
+            .contains(&format_cmd(self.cli, &entry.args))
+        {
+            self.summary.already_completed += 1;
+            return;
+        }
+        let cli = self.cli;
+        if cli.debug {
+            println!("{}", format_debug(cli, &entry));
+            return;
+        }
+        if let Some(format) = cli.plan {
+            println!("{}", plan::format_plan(format, cli, &entry));
+            return;
+        }
+        if cli.interactive && !self.confirmed_all && !self.confirm(&entry) {
+            return;
+        }
+        let entry_dir = cli.results.as_deref().map(|results_dir| {
+            let entry_dir = results::entry_dir(results_dir, &entry);
+            entry.stdout_to = Some(entry_dir.join(results::STDOUT_FILE));
+            entry.stderr_to = Some(entry_dir.join(results::STDERR_FILE));
+            entry_dir
+        });
+        let execution = run_with_retries(cli, &entry, &self.signals);
+        let cmdline = format_cmd(cli, &entry.args);
+        if let Some(entry_dir) = entry_dir {
+            if let Err(error) = results::write_results(&entry_dir, &cmdline, execution.as_ref()) {
+                eprintln!("Failed to write results: {}", error);
+            }
+        }
+        if let (Some(joblog), Some(execution)) = (&mut self.joblog, &execution) {
+            if let Err(error) = joblog.record(entry.number, &cmdline, execution) {
+                eprintln!("Failed to write job log: {}", error);
+            }
+        }
+        match execution.map(|e| (e.termination, e.status.success())) {
+            Some((Some(Termination::TimedOut), _)) => self.summary.timed_out.push(entry.number),
+            Some((Some(Termination::Interrupted), _)) => {
+                self.summary.interrupted.push(entry.number)
+            }
+            Some((None, true)) => self.summary.succeeded += 1,
+            _ => self.summary.failed.push(entry.number),
+        }
+    }
+
+    //Asks user whether to run entry command, entries that will not be run are counted in summary
+    fn confirm(&mut self, entry: &ResolvedEntry) -> bool {
+        let answer = interactive::ask(&format_debug(self.cli, entry), &self.signals);
+        match answer {
+            //Ctrl-C at prompt stops run even when answer was typed after it
+            Ok(_) if self.signals.received().is_some() => {
+                self.summary.not_run += 1;
+                false
+            }
+            Ok(Answer::Yes) => true,
+            Ok(Answer::All) => {
+                self.confirmed_all = true;
+                true
+            }
+            Ok(Answer::No) => {
+                self.summary.skipped += 1;
+                false
+            }
+            //Quitting is user's choice and not a failure, so it is counted as skipping
+            Ok(Answer::Quit) => {
+                self.quit = true;
+                self.summary.skipped += 1;
+                false
+            }
+            Err(error) => {
+                eprintln!("Failed to ask for confirmation: {}", error);
+                self.aborted = true;
+                self.summary.not_run += 1;
+                false
+            }
+        }
+    }
 }
 
-fn run_command(cli: &Cli, cmd_args: &[String]) {
-    let handle = Command::new(&cli.cmd).args(cmd_args).spawn();
+/// Formats command as it would be run, including output redirections
+pub fn format_debug(cli: &Cli, entry: &ResolvedEntry) -> String {
+    let mut words = quoted_cmd_words(cli, &entry.args);
+    if let Some(path) = &entry.stdout_to {
+        words.push(format!("> {}", shell::quote(&path.to_string_lossy())));
+    }
+    if let Some(path) = &entry.stderr_to {
+        words.push(format!("2> {}", shell::quote(&path.to_string_lossy())));
+    }
+    match cli.debug_format {
+        DebugFormat::Shell => words.join(" "),
+        DebugFormat::Lines => words.join(" \\\n  "),
+    }
+}
+
+fn format_cmd(cli: &Cli, args: &[String]) -> String {
+    quoted_cmd_words(cli, args).join(" ")
+}
+
+fn quoted_cmd_words(cli: &Cli, args: &[String]) -> Vec<String> {
+    iter::once(&cli.cmd)
+        .chain(args)
+        .map(|word| shell::quote(word).into_owned())
+        .collect()
+}
+
+fn run_with_retries(cli: &Cli, entry: &ResolvedEntry, signals: &Signals) -> Option<Execution> {
+    let mut delay = cli.retry_delay;
+    for attempt in 1..=cli.retries {
+        let execution = run_command(cli, entry, signals)?;
+        if !should_retry(cli, &execution) || signals.received().is_some() {
+            return Some(execution);
+        }
+        eprintln!(
+            "Command of entry {} failed with exit code {}, retrying in {:?} (retry {} of {})",
+            entry.number,
+            execution.exit_code(),
+            delay,
+            attempt,
+            cli.retries
+        );
+        if sleep_unless_signalled(delay, signals) {
+            return Some(Execution {
+                termination: Some(Termination::Interrupted),
+                ..execution
+            });
+        }
+        delay = delay.saturating_mul(2);
+    }
+    run_command(cli, entry, signals)
+}
+
+//Sleeps in short intervals so signal received during backoff stops retries right away,
+//returns whether signal was received
+fn sleep_unless_signalled(delay: Duration, signals: &Signals) -> bool {
+    let deadline = Instant::now() + delay;
+    while signals.received().is_none() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+        thread::sleep(remaining.min(WAIT_POLL_INTERVAL));
+    }
+    true
+}
+
+fn should_retry(cli: &Cli, execution: &Execution) -> bool {
+    match execution.termination {
+        Some(Termination::Interrupted) => return false,
+        None if execution.status.success() => return false,
+        _ => {}
+    }
+    cli.retry_on.is_empty() || cli.retry_on.contains(&execution.exit_code())
+}
+
+fn run_command(cli: &Cli, entry: &ResolvedEntry, signals: &Signals) -> Option<Execution> {
+    let mut command = Command::new(&cli.cmd);
+    command.args(&entry.args);
+    if let Err(error) = redirect_output(&mut command, entry) {
+        eprintln!("Failed to open output file: {}", error);
+        return None;
+    }
+    let started_at = SystemTime::now();
+    let timer = Instant::now();
+    let handle = command.spawn();
     match handle {
-        Ok(child) => {
-            if let Err(error) = child.wait_with_output() {
+        Ok(mut child) => match wait_for_child(&mut child, cli, signals) {
+            Ok((status, termination)) => Some(Execution {
+                started_at,
+                duration: timer.elapsed(),
+                status,
+                termination,
+            }),
+            Err(error) => {
                 eprintln!("Failed to await for command to finish: {}", error);
+                None
+            }
+        },
+        Err(error) => {
+            eprintln!("Failed to spawn command: {}", error);
+            None
+        }
+    }
+}
+
+//Waits for child, terminating it when it exceeds timeout or when axe received signal.
+//Child is given kill_after time to exit gracefully, then it is killed
+fn wait_for_child(
+    child: &mut Child,
+    cli: &Cli,
+    signals: &Signals,
+) -> io::Result<(ExitStatus, Option<Termination>)> {
+    let timeout_deadline = cli.timeout.map(|timeout| Instant::now() + timeout);
+    let mut termination = None;
+    let mut kill_deadline = None;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, termination));
+        }
+        let now = Instant::now();
+        match kill_deadline {
+            Some(deadline) if now >= deadline => {
+                child.kill()?;
+                return Ok((child.wait()?, termination));
+            }
+            Some(_) => {}
+            None => {
+                if let Some(signal) = signals.received() {
+                    //Ctrl-C in terminal is delivered to child as well, so forwarding it
+                    //would make child receive SIGINT twice
+                    if signal != libc::SIGINT || signals.sent_by_process() {
+                        send_signal(child, signal);
+                    }
+                    termination = Some(Termination::Interrupted);
+                } else if timeout_deadline.is_some_and(|deadline| now >= deadline) {
+                    send_signal(child, libc::SIGTERM);
+                    termination = Some(Termination::TimedOut);
+                }
+                kill_deadline = termination.map(|_| now + cli.kill_after);
             }
         }
-        Err(error) => eprintln!("Failed to spawn command: {}", error),
+        thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
+
+fn send_signal(child: &Child, signal: libc::c_int) {
+    //SAFETY: kill has no memory safety requirements, child is not reaped yet so pid was not reused
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, signal);
+    }
+}
+
+fn redirect_output(command: &mut Command, entry: &ResolvedEntry) -> io::Result<()> {
+    let same_file = entry.stdout_to.is_some() && entry.stdout_to == entry.stderr_to;
+    if let Some(stdout_to) = &entry.stdout_to {
+        let stdout = create_output_file(stdout_to)?;
+        if same_file {
+            //Both streams must share file handle, otherwise they would overwrite each other
+            command.stderr(stdout.try_clone()?);
+        }
+        command.stdout(stdout);
+    }
+    if let Some(stderr_to) = entry.stderr_to.as_ref().filter(|_| !same_file) {
+        command.stderr(create_output_file(stderr_to)?);
+    }
+    Ok(())
+}
+
+fn create_output_file(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
     }
+    File::create(path)
 }
@@ -0,0 +1,284 @@
+use std::cmp::Ordering;
+
+use regex::Regex;
+use thiserror::Error;
+
+use crate::arg_resolver::{ArgumentResolver, Entry, EntryArgs, TemplateError};
+use crate::cli::Cli;
+
+const ARGS_COUNT: &str = "#args";
+//Longer operators go first so <= is not read as <
+const OPERATORS: [(&str, Operator); 8] = [
+    ("!~", Operator::NotMatches),
+    ("==", Operator::Eq),
+    ("!=", Operator::Ne),
+    ("<=", Operator::Le),
+    (">=", Operator::Ge),
+    ("~", Operator::Matches),
+    ("<", Operator::Lt),
+    (">", Operator::Gt),
+];
+
+#[derive(Error, Debug)]
+pub enum FilterError {
+    #[error("Invalid filter '{0}': expected comparison like {{1}}~^prod, {{2}}>=100 or #args==3")]
+    MissingOperator(String),
+    #[error("Invalid filter '{0}': {1}")]
+    Template(String, TemplateError),
+    #[error("Invalid filter '{0}': entry number {{#}} is not known before entries are filtered")]
+    EntryNumber(String),
+    #[error("Invalid filter '{0}': {1}")]
+    Regex(String, regex::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Matches,
+    NotMatches,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+enum Subject<'a> {
+    //{1}, {0:ext} - template resolved into single value
+    Template(ArgumentResolver<'a>),
+    //#args - number of input args
+    ArgsCount,
+}
+
+enum Expected<'a> {
+    Regex(Regex),
+    Value(&'a str),
+}
+
+/// Condition checked for each entry, e.g. {1}~^prod, {2}>=100 or #args==3
+struct Condition<'a> {
+    subject: Subject<'a>,
+    operator: Operator,
+    expected: Expected<'a>,
+}
+
+/// Keeps entries matching all --filter conditions and none of --exclude conditions
+pub struct EntryFilter<'a> {
+    filters: Vec<Condition<'a>>,
+    excludes: Vec<Condition<'a>>,
+    args_separator: &'a str,
+}
+
+impl<'a> EntryFilter<'a> {
+    pub fn new(cli: &'a Cli) -> Result<EntryFilter<'a>, FilterError> {
+        let parse_all = |expressions: &'a [String]| {
+            expressions
+                .iter()
+                .map(|e| Condition::parse(e, cli))
+                .collect::<Result<Vec<Condition>, FilterError>>()
+        };
+        Ok(EntryFilter {
+            filters: parse_all(&cli.filter)?,
+            excludes: parse_all(&cli.exclude)?,
+            args_separator: &cli.args_separator,
+        })
+    }
+
+    /// Entries that are kept
+    pub fn apply(&self, entries: Vec<Entry>) -> Vec<Entry> {
+        if self.filters.is_empty() && self.excludes.is_empty() {
+            return entries;
+        }
+        entries
+            .into_iter()
+            .filter(|entry| self.is_kept(entry))
+            .collect()
+    }
+
+    //Conditions cannot use entry number, so entries are numbered only after filtering
+    fn is_kept(&self, entry: &Entry) -> bool {
+        let entry_args = EntryArgs::new(0, entry, self.args_separator);
+        self.filters.iter().all(|c| c.is_met(&entry_args))
+            && !self.excludes.iter().any(|c| c.is_met(&entry_args))
+    }
+}
+
+impl<'a> Condition<'a> {
+    fn parse(expression: &'a str, cli: &Cli) -> Result<Condition<'a>, FilterError> {
+        let (at, operator_str, operator) = find_operator(expression)
+            .ok_or_else(|| FilterError::MissingOperator(expression.to_string()))?;
+        let subject = match &expression[..at] {
+            ARGS_COUNT => Subject::ArgsCount,
+            template => {
+                let resolver = ArgumentResolver::for_template(template, cli.combine)
+                    .map_err(|error| FilterError::Template(expression.to_string(), error))?;
+                if resolver.uses_entry_number() {
+                    return Err(FilterError::EntryNumber(expression.to_string()));
+                }
+                Subject::Template(resolver)
+            }
+        };
+        let value = &expression[at + operator_str.len()..];
+        let expected = match operator {
+            Operator::Matches | Operator::NotMatches => Regex::new(value)
+                .map(Expected::Regex)
+                .map_err(|error| FilterError::Regex(expression.to_string(), error))?,
+            _ => Expected::Value(value),
+        };
+        Ok(Condition {
+            subject,
+            operator,
+            expected,
+        })
+    }
+
+    //Condition that cannot be evaluated for entry, e.g. because of missing arg, is not met
+    fn is_met(&self, entry_args: &EntryArgs) -> bool {
+        let actual = match &self.subject {
+            Subject::Template(resolver) => match resolver.resolve_single(entry_args) {
+                Ok(actual) => actual,
+                Err(_) => return false,
+            },
+            Subject::ArgsCount => entry_args.args_count().to_string(),
+        };
+        match (&self.expected, self.operator) {
+            (Expected::Regex(regex), Operator::NotMatches) => !regex.is_match(&actual),
+            (Expected::Regex(regex), _) => regex.is_match(&actual),
+            (Expected::Value(expected), operator) => compare(&actual, operator, expected),
+        }
+    }
+}
+
+//Operator is searched outside of placeholders since they may contain <, > or ~ themselves,
+//escaped braces inside placeholder do not open or close it
+fn find_operator(expression: &str) -> Option<(usize, &'static str, Operator)> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (at, c) in expression.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (c, quote) {
+            ('\\', None) if depth > 0 => escaped = true,
+            ('\'' | '"', None) if depth > 0 => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('{', None) => depth += 1,
+            ('}', None) => depth -= 1,
+            (_, None) if depth == 0 => {
+                let operator = OPERATORS
+                    .iter()
+                    .find(|(operator, _)| expression[at..].starts_with(operator));
+                if let Some((operator_str, operator)) = operator {
+                    return Some((at, operator_str, *operator));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+//Values are compared as numbers when both are numbers, otherwise only equality is checked
+fn compare(actual: &str, operator: Operator, expected: &str) -> bool {
+    let ordering = match (actual.trim().parse::<f64>(), expected.trim().parse::<f64>()) {
+        (Ok(actual), Ok(expected)) => actual.partial_cmp(&expected),
+        _ => None,
+    };
+    match (operator, ordering) {
+        (Operator::Eq, Some(ordering)) => ordering == Ordering::Equal,
+        (Operator::Eq, None) => actual == expected,
+        (Operator::Ne, Some(ordering)) => ordering != Ordering::Equal,
+        (Operator::Ne, None) => actual != expected,
+        (Operator::Lt, Some(ordering)) => ordering == Ordering::Less,
+        (Operator::Le, Some(ordering)) => ordering != Ordering::Greater,
+        (Operator::Gt, Some(ordering)) => ordering == Ordering::Greater,
+        (Operator::Ge, Some(ordering)) => ordering != Ordering::Less,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn filtered(args: &[&str], entries: &[&str]) -> Vec<String> {
+        let cli = Cli::parse_from([&["axe"], args].concat());
+        let entry_filter = EntryFilter::new(&cli).unwrap();
+        entry_filter
+            .apply(entries.iter().map(|e| Entry::from(*e)).collect())
+            .iter()
+            .map(Entry::input)
+            .collect()
+    }
+
+    #[test]
+    fn should_find_operator_outside_of_placeholders() {
+        assert_eq!(
+            Some((3, "~", Operator::Matches)),
+            find_operator("{1}~^prod")
+        );
+        assert_eq!(Some((7, ">=", Operator::Ge)), find_operator("{1:>10}>=5"));
+        assert_eq!(Some((8, "!=", Operator::Ne)), find_operator("{0'<'0}x!=a"));
+        assert_eq!(None, find_operator("{0:>5}"));
+        assert_eq!(
+            Some((11, "==", Operator::Eq)),
+            find_operator(r"{0/s/\{/</}==<")
+        );
+    }
+
+    #[test]
+    fn should_compare_numbers_and_text() {
+        assert!(compare("10", Operator::Gt, "9"));
+        assert!(compare("1.0", Operator::Eq, "1"));
+        assert!(compare("abc", Operator::Eq, "abc"));
+        assert!(compare("abc", Operator::Ne, "abd"));
+        assert!(!compare("abc", Operator::Lt, "abd"));
+    }
+
+    #[test]
+    fn should_keep_entries_matching_filters_and_not_excludes() {
+        assert_eq!(
+            vec!["db prod-1 200"],
+            filtered(
+                &["--filter", "{1}~^prod", "--exclude", "{2}<100"],
+                &["web prod-2 50", "db prod-1 200", "db dev-1 300"]
+            )
+        );
+    }
+
+    #[test]
+    fn should_filter_by_args_count_and_skip_entries_with_missing_args() {
+        assert_eq!(
+            vec!["a b"],
+            filtered(&["--filter", "#args==2"], &["a", "a b", "a b c"])
+        );
+        assert_eq!(
+            vec!["x.txt y"],
+            filtered(&["--filter", "{1}!~z"], &["x.txt y", "x.txt z", "x.txt"])
+        );
+    }
+
+    #[test]
+    fn should_fail_on_invalid_expressions() {
+        let cli = Cli::parse_from(["axe", "--filter", "{1}"]);
+        assert!(matches!(
+            EntryFilter::new(&cli),
+            Err(FilterError::MissingOperator(_))
+        ));
+        let cli = Cli::parse_from(["axe", "--exclude", "{1}~(a"]);
+        assert!(matches!(
+            EntryFilter::new(&cli),
+            Err(FilterError::Regex(..))
+        ));
+        let cli = Cli::parse_from(["axe", "--filter", "{#}>=2"]);
+        assert!(matches!(
+            EntryFilter::new(&cli),
+            Err(FilterError::EntryNumber(_))
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    os::fd::AsRawFd,
+};
+
+use crate::signals::Signals;
+
+const POLL_TIMEOUT_MS: libc::c_int = 10;
+
+/// User decision whether command should be run
+#[derive(Debug, PartialEq, Eq)]
+pub enum Answer {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+/// Asks user on terminal whether command should be run. Terminal is used directly
+/// because stdin usually carries input entries. Asks again until answer is valid,
+/// signal received while waiting for answer quits
+pub fn ask(cmdline: &str, signals: &Signals) -> io::Result<Answer> {
+    let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    let mut reader = BufReader::new(tty.try_clone()?);
+    let mut writer = tty;
+    loop {
+        write!(writer, "{} [y/n/a/q]? ", cmdline)?;
+        writer.flush()?;
+        if !wait_for_input(&reader, signals)? {
+            writeln!(writer)?;
+            return Ok(Answer::Quit);
+        }
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(Answer::Quit);
+        }
+        if let Some(answer) = parse_answer(&line) {
+            return Ok(answer);
+        }
+    }
+}
+
+//Signal handler restarts blocking reads, so terminal is polled to notice Ctrl-C at prompt.
+//Returns false when signal was received before any input
+fn wait_for_input(reader: &BufReader<File>, signals: &Signals) -> io::Result<bool> {
+    if !reader.buffer().is_empty() {
+        return Ok(true);
+    }
+    let mut poll_fd = libc::pollfd {
+        fd: reader.get_ref().as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    while signals.received().is_none() {
+        //SAFETY: exactly one valid pollfd is passed and descriptor is open for the whole call
+        match unsafe { libc::poll(&mut poll_fd, 1, POLL_TIMEOUT_MS) } {
+            -1 => {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error);
+                }
+            }
+            0 => {}
+            _ => return Ok(true),
+        }
+    }
+    Ok(false)
+}
+
+fn parse_answer(line: &str) -> Option<Answer> {
+    match line.trim().to_lowercase().as_str() {
+        "y" | "yes" => Some(Answer::Yes),
+        "n" | "no" => Some(Answer::No),
+        "a" | "all" => Some(Answer::All),
+        "q" | "quit" => Some(Answer::Quit),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_answers() {
+        assert_eq!(Some(Answer::Yes), parse_answer("y\n"));
+        assert_eq!(Some(Answer::No), parse_answer(" No "));
+        assert_eq!(Some(Answer::All), parse_answer("a"));
+        assert_eq!(Some(Answer::Quit), parse_answer("QUIT\n"));
+    }
+
+    #[test]
+    fn should_not_parse_invalid_answers() {
+        assert_eq!(None, parse_answer("\n"));
+        assert_eq!(None, parse_answer("maybe"));
+    }
+}
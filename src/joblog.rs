@@ -0,0 +1,101 @@
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    time::UNIX_EPOCH,
+};
+
+use crate::cmd_exe::Execution;
+
+const HEADER: &str = "Seq\tStarttime\tJobRuntime\tExitval\tCommand";
+
+/// Tab separated log with one record per executed entry, appended as commands finish
+pub struct JobLog {
+    file: File,
+}
+
+impl JobLog {
+    pub fn open(path: &Path) -> io::Result<JobLog> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", HEADER)?;
+        }
+        Ok(JobLog { file })
+    }
+
+    pub fn record(
+        &mut self,
+        entry_number: usize,
+        cmdline: &str,
+        execution: &Execution,
+    ) -> io::Result<()> {
+        let started_at = execution
+            .started_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        writeln!(
+            self.file,
+            "{}\t{:.3}\t{:.3}\t{}\t{}",
+            entry_number,
+            started_at.as_secs_f64(),
+            execution.duration.as_secs_f64(),
+            execution.exit_code(),
+            cmdline
+        )
+    }
+}
+
+/// Reads commands which exited with 0. Missing log means nothing was completed yet.
+/// Commands are compared instead of entry numbers, since numbers change when input is filtered,
+/// sorted or sampled differently than in resumed run
+pub fn read_completed_commands(path: &Path) -> io::Result<HashSet<String>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(error) => return Err(error),
+    };
+    let mut completed = HashSet::new();
+    for line in BufReader::new(file).lines() {
+        if let Some(cmdline) = parse_completed_command(&line?) {
+            completed.insert(cmdline);
+        }
+    }
+    Ok(completed)
+}
+
+//Command is the last column, so it is taken whole even when it contains tabs
+fn parse_completed_command(line: &str) -> Option<String> {
+    let mut columns = line.splitn(5, '\t');
+    match (columns.nth(3)?, columns.next()?) {
+        ("0", cmdline) => Some(cmdline.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_successfully_completed_command() {
+        assert_eq!(
+            Some("echo a b".to_string()),
+            parse_completed_command("12\t1718000000.123\t0.500\t0\techo a b")
+        );
+        assert_eq!(
+            Some("echo 'a\tb'".to_string()),
+            parse_completed_command("1\t1718000000.123\t0.500\t0\techo 'a\tb'")
+        );
+    }
+
+    #[test]
+    fn should_skip_failed_entries_and_header() {
+        assert_eq!(
+            None,
+            parse_completed_command("3\t1718000000.123\t0.500\t1\tfalse")
+        );
+        assert_eq!(None, parse_completed_command(HEADER));
+        assert_eq!(None, parse_completed_command(""));
+    }
+}
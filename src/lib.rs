@@ -1,4 +1,13 @@
 pub mod arg_resolver;
 pub mod cli;
 pub mod cmd_exe;
+pub mod filter;
+pub mod interactive;
+pub mod joblog;
+pub mod plan;
+pub mod results;
+pub mod selection;
+pub mod shell;
+pub mod signals;
 pub mod stdin;
+pub mod summary;
@@ -1,15 +1,78 @@
-use axe_cli::arg_resolver::resolve_cmd_args;
+use axe_cli::arg_resolver::{explain_templates, CmdResolver, Entry};
 use axe_cli::cli::Cli;
-use axe_cli::cmd_exe::execute_cmd;
+use axe_cli::cmd_exe::{format_debug, CmdExecutor};
+use axe_cli::filter::EntryFilter;
+use axe_cli::selection::EntrySelection;
 use axe_cli::stdin::read_entries;
 use clap::Parser;
+use std::process;
+
+//Same as xargs when any command failed
+const FAILED_ENTRIES_EXIT_CODE: i32 = 123;
 
 fn main() {
     let cli = Cli::parse();
+    if !cli.explain.is_empty() {
+        let explanation = explain_templates(&cli.explain, cli.combine);
+        print!("{}", explanation.text);
+        if !explanation.all_valid {
+            process::exit(1);
+        }
+        return;
+    }
+    let cmd_resolver = CmdResolver::new(&cli).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let entry_filter = EntryFilter::new(&cli).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let entry_selection = EntrySelection::new(&cli).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    if let Some(sample) = &cli.check {
+        if let Some(sample) = sample {
+            match cmd_resolver.resolve_entry(1, Entry::from(sample.as_str())) {
+                Ok(entry) => println!("{}", format_debug(&cli, &entry)),
+                Err(error) => {
+                    eprintln!("Failed to resolve sample entry: {}", error);
+                    process::exit(1);
+                }
+            }
+        }
+        return;
+    }
     let stdin_entries = read_entries(&cli);
-    let resolved_cmd_args = resolve_cmd_args(stdin_entries, &cli);
+    let read_count = stdin_entries.len();
+    let stdin_entries = entry_filter.apply(stdin_entries);
+    let filtered_out = read_count - stdin_entries.len();
+    let stdin_entries = entry_selection.apply(stdin_entries);
+    let resolved_cmd_args = cmd_resolver
+        .resolve_entries(stdin_entries)
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(1);
+        });
+    let mut executor = CmdExecutor::new(&cli).unwrap_or_else(|error| {
+        eprintln!("Failed to prepare commands execution: {}", error);
+        process::exit(1);
+    });
+    executor.filtered_out(filtered_out);
 
     for cmd_args in resolved_cmd_args {
-        execute_cmd(&cli, cmd_args);
+        executor.execute(cmd_args);
+    }
+    let summary = executor.summary();
+    let dry_run = cli.debug || cli.plan.is_some();
+    if !dry_run && summary.should_print() {
+        eprintln!("{}", summary);
+    }
+    if let Some(signal) = executor.received_signal() {
+        process::exit(128 + signal);
+    }
+    if !summary.is_success() {
+        process::exit(FAILED_ENTRIES_EXIT_CODE);
     }
 }
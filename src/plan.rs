@@ -0,0 +1,39 @@
+use std::borrow::Cow;
+
+use serde::Serialize;
+
+use crate::{
+    arg_resolver::ResolvedEntry,
+    cli::{Cli, PlanFormat},
+};
+
+/// What would be executed for single entry, args are kept as array so their boundaries are preserved
+#[derive(Serialize)]
+struct PlannedEntry<'a> {
+    entry: usize,
+    input: &'a str,
+    input_args: &'a [String],
+    cmd: &'a str,
+    args: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stdout_to: Option<Cow<'a, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stderr_to: Option<Cow<'a, str>>,
+}
+
+pub fn format_plan(format: PlanFormat, cli: &Cli, entry: &ResolvedEntry) -> String {
+    let planned = PlannedEntry {
+        entry: entry.number,
+        input: &entry.input,
+        input_args: &entry.input_args,
+        cmd: &cli.cmd,
+        args: &entry.args,
+        stdout_to: entry.stdout_to.as_ref().map(|p| p.to_string_lossy()),
+        stderr_to: entry.stderr_to.as_ref().map(|p| p.to_string_lossy()),
+    };
+    match format {
+        PlanFormat::Json => {
+            serde_json::to_string(&planned).expect("Planned entry is always serializable")
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use crate::{arg_resolver::ResolvedEntry, cmd_exe::Execution};
+
+pub const STDOUT_FILE: &str = "stdout";
+pub const STDERR_FILE: &str = "stderr";
+const EXIT_CODE_FILE: &str = "exit_code";
+const CMDLINE_FILE: &str = "cmdline";
+const START_TIME_FILE: &str = "start_time";
+const DURATION_FILE: &str = "duration";
+const MAX_DIR_NAME_ARGS_LEN: usize = 64;
+
+/// Directory of single entry results, named after entry number and its sanitized input args
+/// so it stays unique and still easy to find, e.g. 2_photo.jpg_1024
+pub fn entry_dir(results_dir: &Path, entry: &ResolvedEntry) -> PathBuf {
+    let args = sanitize(&entry.input_args.join("_"));
+    if args.is_empty() {
+        results_dir.join(entry.number.to_string())
+    } else {
+        results_dir.join(format!("{}_{}", entry.number, args))
+    }
+}
+
+/// Writes command line and, if command was run, its exit code and timing.
+/// Stdout and stderr files are written by command itself.
+pub fn write_results(
+    entry_dir: &Path,
+    cmdline: &str,
+    execution: Option<&Execution>,
+) -> io::Result<()> {
+    fs::create_dir_all(entry_dir)?;
+    fs::write(entry_dir.join(CMDLINE_FILE), format!("{}\n", cmdline))?;
+    if let Some(execution) = execution {
+        let started_at = execution
+            .started_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        fs::write(
+            entry_dir.join(EXIT_CODE_FILE),
+            format!("{}\n", execution.exit_code()),
+        )?;
+        fs::write(
+            entry_dir.join(START_TIME_FILE),
+            format!("{:.3}\n", started_at.as_secs_f64()),
+        )?;
+        fs::write(
+            entry_dir.join(DURATION_FILE),
+            format!("{:.3}\n", execution.duration.as_secs_f64()),
+        )?;
+    }
+    Ok(())
+}
+
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .take(MAX_DIR_NAME_ARGS_LEN)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(number: usize, input_args: &[&str]) -> ResolvedEntry {
+        ResolvedEntry {
+            number,
+            input: input_args.join(" "),
+            input_args: input_args.iter().map(|a| a.to_string()).collect(),
+            args: Vec::new(),
+            stdout_to: None,
+            stderr_to: None,
+        }
+    }
+
+    #[test]
+    fn should_name_entry_dir_after_number_and_input_args() {
+        assert_eq!(
+            PathBuf::from("out/3_a.txt_b"),
+            entry_dir(Path::new("out"), &entry(3, &["a.txt", "b"]))
+        );
+    }
+
+    #[test]
+    fn should_sanitize_input_args_in_entry_dir_name() {
+        assert_eq!(
+            PathBuf::from("out/1_.._etc_passwd_x_y"),
+            entry_dir(Path::new("out"), &entry(1, &["../etc/passwd", "x y"]))
+        );
+        assert_eq!(
+            PathBuf::from("out/2"),
+            entry_dir(Path::new("out"), &entry(2, &[]))
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use rand::{rngs::StdRng, seq::index, SeedableRng};
+
+use crate::arg_resolver::{ArgumentResolver, Entry, EntryArgs, TemplateError};
+use crate::cli::Cli;
+
+/// Deduplicates, sorts and samples entries before they are resolved, in that order
+pub struct EntrySelection<'a> {
+    unique: Option<EntryKey<'a>>,
+    sort: Option<EntryKey<'a>>,
+    sample: Option<usize>,
+    seed: Option<u64>,
+    args_separator: &'a str,
+}
+
+//Template that entries are compared by, whole entry is compared when it is not given
+struct EntryKey<'a> {
+    resolver: Option<ArgumentResolver<'a>>,
+}
+
+impl<'a> EntrySelection<'a> {
+    pub fn new(cli: &'a Cli) -> Result<EntrySelection<'a>, TemplateError> {
+        let entry_key = |key: &'a Option<Option<String>>| {
+            key.as_ref().map(|key| EntryKey::new(key, cli)).transpose()
+        };
+        Ok(EntrySelection {
+            unique: entry_key(&cli.unique)?,
+            sort: entry_key(&cli.sort)?,
+            sample: cli.sample,
+            seed: cli.seed,
+            args_separator: &cli.args_separator,
+        })
+    }
+
+    pub fn apply(&self, mut entries: Vec<Entry>) -> Vec<Entry> {
+        if let Some(key) = &self.unique {
+            let keys = self.keys(key, &entries);
+            let mut seen = HashSet::new();
+            //Entries which key cannot be resolved are never treated as duplicates
+            entries = keys
+                .into_iter()
+                .zip(entries)
+                .filter(|(key, _)| key.as_ref().is_none_or(|key| seen.insert(key.clone())))
+                .map(|(_, entry)| entry)
+                .collect();
+        }
+        if let Some(key) = &self.sort {
+            let keys = self.keys(key, &entries);
+            let mut keyed = keys.into_iter().zip(entries).collect::<Vec<_>>();
+            let numeric = keyed
+                .iter()
+                .all(|(key, _)| key.as_ref().is_none_or(|k| k.parse::<f64>().is_ok()));
+            keyed.sort_by(|(a, _), (b, _)| compare_keys(a, b, numeric));
+            entries = keyed.into_iter().map(|(_, entry)| entry).collect();
+        }
+        if let Some(amount) = self.sample {
+            entries = self.sample(entries, amount);
+        }
+        entries
+    }
+
+    fn keys(&self, key: &EntryKey, entries: &[Entry]) -> Vec<Option<String>> {
+        entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| key.resolve(idx + 1, entry, self.args_separator))
+            .collect()
+    }
+
+    //Sampled entries keep their order so sorting is not lost
+    fn sample(&self, entries: Vec<Entry>, amount: usize) -> Vec<Entry> {
+        if amount >= entries.len() {
+            return entries;
+        }
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let mut picked = index::sample(&mut rng, entries.len(), amount).into_vec();
+        picked.sort_unstable();
+        let mut picked = picked.into_iter().peekable();
+        entries
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| picked.next_if_eq(idx).is_some())
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+}
+
+impl<'a> EntryKey<'a> {
+    fn new(key: &'a Option<String>, cli: &Cli) -> Result<EntryKey<'a>, TemplateError> {
+        let resolver = key
+            .as_deref()
+            .map(|key| ArgumentResolver::for_template(key, cli.combine))
+            .transpose()?;
+        Ok(EntryKey { resolver })
+    }
+
+    fn resolve(&self, number: usize, entry: &Entry, args_separator: &str) -> Option<String> {
+        match &self.resolver {
+            Some(resolver) => {
+                let entry_args = EntryArgs::new(number, entry, args_separator);
+                resolver.resolve_single(&entry_args).ok()
+            }
+            None => Some(entry.input()),
+        }
+    }
+}
+
+//Keys are compared as numbers only when all of them are numbers, entries without key go last
+fn compare_keys(a: &Option<String>, b: &Option<String>, numeric: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if numeric => {
+            let (a, b) = (a.parse::<f64>().unwrap(), b.parse::<f64>().unwrap());
+            a.total_cmp(&b)
+        }
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn selected(args: &[&str], entries: &[&str]) -> Vec<String> {
+        let cli = Cli::parse_from([&["axe"], args].concat());
+        let selection = EntrySelection::new(&cli).unwrap();
+        selection
+            .apply(entries.iter().map(|e| Entry::from(*e)).collect())
+            .iter()
+            .map(Entry::input)
+            .collect()
+    }
+
+    #[test]
+    fn should_keep_first_of_duplicated_entries() {
+        assert_eq!(
+            vec!["a 1", "b 2"],
+            selected(&["--unique"], &["a 1", "b 2", "a 1"])
+        );
+        assert_eq!(
+            vec!["a 1", "b 1", "c"],
+            selected(&["--unique={0}"], &["a 1", "b 1", "a 2", "c"])
+        );
+        assert_eq!(
+            vec!["a 1", "b", "c"],
+            selected(&["--unique={1}"], &["a 1", "b", "c", "d 1"])
+        );
+    }
+
+    #[test]
+    fn should_sort_entries_by_numbers_or_text() {
+        assert_eq!(
+            vec!["b 2", "c 10", "a"],
+            selected(&["--sort={1}"], &["c 10", "a", "b 2"])
+        );
+        assert_eq!(
+            vec!["a", "b 2", "c 10"],
+            selected(&["--sort"], &["c 10", "a", "b 2"])
+        );
+    }
+
+    #[test]
+    fn should_sample_same_entries_for_same_seed() {
+        let entries = (1..=20).map(|n| n.to_string()).collect::<Vec<String>>();
+        let entries = entries.iter().map(|e| e.as_str()).collect::<Vec<&str>>();
+        let sampled = selected(&["--sample", "5", "--seed", "7"], &entries);
+        assert_eq!(5, sampled.len());
+        assert_eq!(
+            sampled,
+            selected(&["--sample", "5", "--seed", "7"], &entries)
+        );
+        let mut sorted = sampled.clone();
+        sorted.sort_by_key(|n| n.parse::<usize>().unwrap());
+        assert_eq!(sorted, sampled);
+    }
+}
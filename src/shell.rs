@@ -0,0 +1,33 @@
+use std::borrow::Cow;
+
+/// Quotes value for POSIX shell so it is passed to command as single argument.
+/// Values consisting only of safe characters are left untouched for readability
+pub fn quote(value: &str) -> Cow<'_, str> {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "@%+=:,./-_".contains(c);
+    if !value.is_empty() && value.chars().all(is_safe) {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(format!("'{}'", value.replace('\'', r"'\''")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_not_quote_safe_values() {
+        assert_eq!("abc", quote("abc"));
+        assert_eq!("out/a-1.log", quote("out/a-1.log"));
+        assert_eq!("--ids=a,b", quote("--ids=a,b"));
+    }
+
+    #[test]
+    fn should_quote_values_with_special_characters() {
+        assert_eq!("''", quote(""));
+        assert_eq!("'a b'", quote("a b"));
+        assert_eq!("'$HOME'", quote("$HOME"));
+        assert_eq!("'{0}'", quote("{0}"));
+        assert_eq!(r"'it'\''s'", quote("it's"));
+        assert_eq!("'a\nb'", quote("a\nb"));
+    }
+}
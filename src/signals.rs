@@ -0,0 +1,53 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use libc::{SIGINT, SIGTERM};
+
+/// Records SIGINT and SIGTERM received by axe so they can be forwarded to running command
+/// and remaining entries can be skipped
+pub struct Signals {
+    received: Arc<AtomicUsize>,
+    sent_by_process: Arc<AtomicBool>,
+}
+
+impl Signals {
+    pub fn register() -> io::Result<Signals> {
+        let received = Arc::new(AtomicUsize::new(0));
+        let sent_by_process = Arc::new(AtomicBool::new(false));
+        for signal in [SIGINT, SIGTERM] {
+            let received = Arc::clone(&received);
+            let sent_by_process = Arc::clone(&sent_by_process);
+            //SAFETY: action only stores into atomics, which is async-signal-safe, and reads
+            //sender pid that kernel fills in for every signal
+            unsafe {
+                signal_hook_registry::register_sigaction(signal, move |info| {
+                    sent_by_process.store(info.si_pid() != 0, Ordering::SeqCst);
+                    received.store(signal as usize, Ordering::SeqCst);
+                })?;
+            }
+        }
+        Ok(Signals {
+            received,
+            sent_by_process,
+        })
+    }
+
+    /// Last signal received, if any
+    pub fn received(&self) -> Option<i32> {
+        match self.received.load(Ordering::SeqCst) {
+            0 => None,
+            signal => Some(signal as i32),
+        }
+    }
+
+    /// Whether last signal was sent by other process, e.g. with kill. Signals generated by terminal,
+    /// like SIGINT on Ctrl-C, have no sender and are delivered to whole foreground process group
+    pub fn sent_by_process(&self) -> bool {
+        self.sent_by_process.load(Ordering::SeqCst)
+    }
+}
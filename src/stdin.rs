@@ -5,14 +5,37 @@ use std::{
 
 use clap::error::Result;
 
-use crate::cli::{Cli, EntriesOptions};
+use crate::arg_resolver::Entry;
+use crate::cli::{Cli, EntriesOptions, WindowOptions, WindowUnit};
 
-pub fn read_entries(cli: &Cli) -> Vec<String> {
-    let stdin_lines = read_input_lines(&cli.args_file);
-    split_input_lines_into_entries(stdin_lines, &cli.entries, &cli.args_separator)
+pub fn read_entries(cli: &Cli) -> Vec<Entry> {
+    let sources = read_sources(cli)
+        .into_iter()
+        .map(|lines| match cli.window.size {
+            Some(_) => split_input_lines_into_windows(lines, &cli.window, &cli.args_separator),
+            None => split_input_lines_into_entries(lines, &cli.entries, &cli.args_separator),
+        })
+        .collect();
+    combine_sources(sources, cli.link)
 }
 
-fn read_input_lines(args_file: &Option<PathBuf>) -> Vec<String> {
+//Standard input is read only when no file nor values are given
+fn read_sources(cli: &Cli) -> Vec<Vec<String>> {
+    if cli.args_file.is_empty() && cli.values.is_empty() {
+        return vec![read_input_lines(None)];
+    }
+    let files = cli
+        .args_file
+        .iter()
+        .map(|path| read_input_lines(Some(path)));
+    let values = cli
+        .values
+        .iter()
+        .map(|values| values.split(',').map(|v| v.to_string()).collect());
+    files.chain(values).collect()
+}
+
+fn read_input_lines(args_file: Option<&PathBuf>) -> Vec<String> {
     let reader: Box<dyn BufRead> = match args_file {
         Some(path) => {
             //FIXME: handle error
@@ -27,14 +50,47 @@ fn read_input_lines(args_file: &Option<PathBuf>) -> Vec<String> {
         .unwrap_or_else(|_| {
             panic!(
                 "Could not read args from {}",
-                args_file
-                    .as_ref()
-                    .and_then(|p| p.to_str())
-                    .unwrap_or("stdin")
+                args_file.and_then(|p| p.to_str()).unwrap_or("stdin")
             )
         })
 }
 
+//Entries of sources are combined into one entry, each source stays apart so it is available as {Sn}
+fn combine_sources(mut sources: Vec<Vec<String>>, link: bool) -> Vec<Entry> {
+    if sources.len() == 1 {
+        return sources.remove(0).into_iter().map(Entry::from).collect();
+    }
+    if sources.iter().any(|source| source.is_empty()) {
+        return Vec::new();
+    }
+    if link {
+        let longest = sources.iter().map(|source| source.len()).max().unwrap_or(0);
+        return (0..longest)
+            .map(|idx| {
+                let entries = sources
+                    .iter()
+                    .map(|source| source[idx % source.len()].clone());
+                Entry::combined(entries.collect())
+            })
+            .collect();
+    }
+    sources
+        .into_iter()
+        .fold(vec![Vec::new()], |combined: Vec<Vec<String>>, source| {
+            combined
+                .iter()
+                .flat_map(|entries| {
+                    source
+                        .iter()
+                        .map(|next| [entries.as_slice(), std::slice::from_ref(next)].concat())
+                })
+                .collect()
+        })
+        .into_iter()
+        .map(Entry::combined)
+        .collect()
+}
+
 fn split_input_lines_into_entries(
     stdin_lines: Vec<String>,
     entries: &EntriesOptions,
@@ -43,12 +99,14 @@ fn split_input_lines_into_entries(
     match (
         entries.single_entry,
         entries.entry_size,
+        entries.lines,
         entries.entries_separator.as_str(),
     ) {
-        (true, _, _) => vec![stdin_lines.join(args_separator)],
-        (_, size, _) if size > 0 => split_by_size(stdin_lines, size, args_separator),
-        (_, _, "\n") => stdin_lines,
-        (_, _, entry_sep) => stdin_lines
+        (true, _, _, _) => vec![stdin_lines.join(args_separator)],
+        (_, size, _, _) if size > 0 => split_by_size(stdin_lines, size, args_separator),
+        (_, _, lines, _) if lines > 0 => stdin_lines.chunks(lines).map(|c| c.join("\n")).collect(),
+        (_, _, _, "\n") => stdin_lines,
+        (_, _, _, entry_sep) => stdin_lines
             .join("")
             .split(&entry_sep)
             .map(|l| l.to_owned())
@@ -65,6 +123,42 @@ fn split_by_size(stdin_lines: Vec<String>, size: usize, sep: &str) -> Vec<String
     all_args.chunks(size).map(|c| c.join(sep)).collect()
 }
 
+fn split_input_lines_into_windows(
+    stdin_lines: Vec<String>,
+    window: &WindowOptions,
+    sep: &str,
+) -> Vec<String> {
+    let items: Vec<String> = match window.window_by {
+        WindowUnit::Args => stdin_lines
+            .iter()
+            .flat_map(|l| l.split(sep).map(|s| s.to_string()))
+            .collect(),
+        WindowUnit::Lines => stdin_lines,
+    };
+    let size = window.size.unwrap_or(1);
+    //Lines are kept apart so they are available as {L0}, {L1}, ...
+    let join_by = match window.window_by {
+        WindowUnit::Args => sep,
+        WindowUnit::Lines => "\n",
+    };
+    windows(&items, size, window.step)
+        .into_iter()
+        .map(|w| w.join(join_by))
+        .collect()
+}
+
+//Remaining items that do not fill last window are dropped, so every window has all args
+//that command template expects
+fn windows<T>(items: &[T], size: usize, step: usize) -> Vec<&[T]> {
+    let mut windows = Vec::new();
+    let mut start = 0;
+    while start + size <= items.len() {
+        windows.push(&items[start..start + size]);
+        start += step;
+    }
+    windows
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -77,6 +171,7 @@ mod tests {
             single_entry: false,
             entries_separator: "\n".to_string(),
             entry_size: 0,
+            lines: 0,
         };
         let expected = stdin_lines.clone();
         let actual = split_input_lines_into_entries(stdin_lines, &entries_options, " ");
@@ -89,6 +184,7 @@ mod tests {
             single_entry: false,
             entries_separator: ";".to_string(),
             entry_size: 0,
+            lines: 0,
         };
         let stdin_lines = vec!["a b c;d e f;".to_string(), "g h i;j k l".to_string()];
         let expected = vec![
@@ -107,6 +203,7 @@ mod tests {
             single_entry: true,
             entries_separator: "\n".to_string(),
             entry_size: 0,
+            lines: 0,
         };
         let stdin_lines = vec!["a,b,c".to_string(), "d,e,f".to_string()];
         let expected = vec!["a,b,c,d,e,f".to_string()];
@@ -120,6 +217,7 @@ mod tests {
             single_entry: false,
             entries_separator: "\n".to_string(),
             entry_size: 2,
+            lines: 0,
         };
         let stdin_lines = vec!["a;b;c".to_string(), "d;e;f;g".to_string()];
         let expected = vec![
@@ -131,4 +229,89 @@ mod tests {
         let actual = split_input_lines_into_entries(stdin_lines, &entries_options, ";");
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn should_split_items_into_overlapping_windows() {
+        let items = [1, 2, 3, 4, 5];
+        let expected: Vec<&[i32]> = vec![&[1, 2], &[2, 3], &[3, 4], &[4, 5]];
+        assert_eq!(expected, windows(&items, 2, 1));
+        let expected: Vec<&[i32]> = vec![&[1, 2, 3], &[3, 4, 5]];
+        assert_eq!(expected, windows(&items, 3, 2));
+        let expected: Vec<&[i32]> = vec![&[1, 2], &[3, 4]];
+        assert_eq!(expected, windows(&items, 2, 2));
+        let expected: Vec<&[i32]> = vec![&[1, 2], &[4, 5]];
+        assert_eq!(expected, windows(&items, 2, 3));
+        let expected: Vec<&[i32]> = vec![&[1, 2, 3, 4, 5]];
+        assert_eq!(expected, windows(&items, 5, 1));
+        assert!(windows(&items, 10, 1).is_empty());
+        assert!(windows::<i32>(&[], 2, 1).is_empty());
+    }
+
+    #[test]
+    fn should_split_input_lines_into_windows_of_args_or_lines() {
+        let stdin_lines = vec!["a b".to_string(), "c".to_string(), "d e".to_string()];
+        let mut window = WindowOptions {
+            size: Some(2),
+            step: 1,
+            window_by: WindowUnit::Args,
+        };
+        assert_eq!(
+            vec!["a b", "b c", "c d", "d e"],
+            split_input_lines_into_windows(stdin_lines.clone(), &window, " ")
+        );
+        window.window_by = WindowUnit::Lines;
+        assert_eq!(
+            vec!["a b\nc", "c\nd e"],
+            split_input_lines_into_windows(stdin_lines, &window, " ")
+        );
+    }
+
+    #[test]
+    fn should_group_whole_lines_into_entries() {
+        let entries_options = EntriesOptions {
+            single_entry: false,
+            entries_separator: "\n".to_string(),
+            entry_size: 0,
+            lines: 2,
+        };
+        let stdin_lines = vec!["a b".to_string(), "c".to_string(), "d e".to_string()];
+        let expected = vec!["a b\nc".to_string(), "d e".to_string()];
+        let actual = split_input_lines_into_entries(stdin_lines, &entries_options, " ");
+        assert_eq!(expected, actual);
+    }
+
+    fn combined(sources: &[&str]) -> Entry {
+        Entry::combined(sources.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn should_combine_sources_each_with_each() {
+        let sources = vec![
+            vec!["a".to_string(), "b".to_string()],
+            vec!["1".to_string(), "2".to_string()],
+        ];
+        let expected = vec![
+            combined(&["a", "1"]),
+            combined(&["a", "2"]),
+            combined(&["b", "1"]),
+            combined(&["b", "2"]),
+        ];
+        assert_eq!(expected, combine_sources(sources, false));
+    }
+
+    #[test]
+    fn should_link_sources_repeating_shorter_ones() {
+        let sources = vec![
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            vec!["1".to_string()],
+        ];
+        let expected = vec![
+            combined(&["a", "1"]),
+            combined(&["b", "1"]),
+            combined(&["c", "1"]),
+        ];
+        assert_eq!(expected, combine_sources(sources, true));
+        let sources = vec![vec!["a".to_string()], vec![]];
+        assert!(combine_sources(sources, true).is_empty());
+    }
 }
@@ -0,0 +1,128 @@
+use std::fmt::Display;
+
+/// Counts of entries by outcome, printed when run did not fully succeed or some entries were left out
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    pub succeeded: usize,
+    pub failed: Vec<usize>,
+    pub timed_out: Vec<usize>,
+    pub interrupted: Vec<usize>,
+    pub skipped: usize,
+    pub filtered_out: usize,
+    pub already_completed: usize,
+    pub not_run: usize,
+}
+
+impl RunSummary {
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+            && self.timed_out.is_empty()
+            && self.interrupted.is_empty()
+            && self.not_run == 0
+    }
+
+    /// Successful run is summarized only when some entries were skipped, filtered out or resumed
+    pub fn should_print(&self) -> bool {
+        !self.is_success()
+            || self.skipped > 0
+            || self.filtered_out > 0
+            || self.already_completed > 0
+    }
+}
+
+impl Display for RunSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} succeeded", self.succeeded)?;
+        write_entries(f, "failed", &self.failed)?;
+        write_entries(f, "timed out", &self.timed_out)?;
+        write_entries(f, "interrupted", &self.interrupted)?;
+        if self.skipped > 0 {
+            write!(f, ", {} skipped", self.skipped)?;
+        }
+        if self.filtered_out > 0 {
+            write!(f, ", {} filtered out", self.filtered_out)?;
+        }
+        if self.already_completed > 0 {
+            write!(f, ", {} already completed", self.already_completed)?;
+        }
+        if self.not_run > 0 {
+            write!(f, ", {} not run", self.not_run)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_entries(
+    f: &mut std::fmt::Formatter<'_>,
+    label: &str,
+    entries: &[usize],
+) -> std::fmt::Result {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let numbers = entries
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+    write!(f, ", {} {} (entries: {})", entries.len(), label, numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_display_failed_and_timed_out_entries() {
+        let summary = RunSummary {
+            succeeded: 5,
+            failed: vec![2, 4],
+            timed_out: vec![7],
+            ..Default::default()
+        };
+        assert_eq!(
+            "5 succeeded, 2 failed (entries: 2, 4), 1 timed out (entries: 7)",
+            summary.to_string()
+        );
+    }
+
+    #[test]
+    fn should_display_interrupted_and_not_run_entries() {
+        let summary = RunSummary {
+            succeeded: 2,
+            interrupted: vec![3],
+            skipped: 1,
+            filtered_out: 2,
+            already_completed: 3,
+            not_run: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            "2 succeeded, 1 interrupted (entries: 3), 1 skipped, 2 filtered out, 3 already completed, 4 not run",
+            summary.to_string()
+        );
+        assert!(!summary.is_success());
+    }
+
+    #[test]
+    fn should_print_successful_summary_when_entries_were_left_out() {
+        let summary = RunSummary {
+            succeeded: 1,
+            filtered_out: 2,
+            ..Default::default()
+        };
+        assert!(summary.is_success());
+        assert!(summary.should_print());
+    }
+
+    #[test]
+    fn should_display_only_succeeded_entries() {
+        let summary = RunSummary {
+            succeeded: 3,
+            ..Default::default()
+        };
+        assert_eq!("3 succeeded", summary.to_string());
+        assert!(summary.is_success());
+        assert!(!summary.should_print());
+    }
+}
@@ -1,6 +1,10 @@
 use std::{
-    io::Write,
+    fs,
+    io::{self, Write},
+    os::unix::process::CommandExt,
     process::{Command, Output, Stdio},
+    ptr,
+    time::{Duration, Instant},
 };
 
 #[test]
@@ -33,12 +37,346 @@ fn should_allow_to_read_input_as_entries_with_size() {
     assert_eq!(output, vec!("echo a b", "echo c d", "echo e f"));
 }
 
+#[test]
+fn should_allow_to_zip_array_placeholders() {
+    let output = execute_with(&["-d", "--combine", "zip", "echo", "{.0}-{.1}"], "a.x b.y");
+    assert_eq!(output, vec!("echo a-x b-y"));
+}
+
+#[test]
+fn should_join_array_placeholder_into_single_arg() {
+    let output = execute_with(&["-d", "echo", "--ids={.0|join:,}"], "a.x b.y c.z");
+    assert_eq!(output, vec!("echo --ids=a,b,c"));
+}
+
+#[test]
+fn should_split_args_by_quoted_separator() {
+    let output = execute_with(&["-d", "echo", "{0'1x'1}", r#"{1"}"0}"#], "v1x2 a}b");
+    assert_eq!(output, vec!("echo 2 a"));
+}
+
+#[test]
+fn should_split_args_in_multiple_levels() {
+    let output = execute_with(&["-d", "echo", "{0/2.0}", "{/1_}"], "a/b/c.txt d/e_f.g");
+    assert_eq!(output, vec!("echo c b e f.g"));
+}
+
+#[test]
+fn should_resolve_path_parts() {
+    let output = execute_with(
+        &["-d", "tar", "-xf", "{0}", "-C", "{0:parent}/{0:stem:stem}"],
+        "backups/v1.2/archive.tar.gz",
+    );
+    assert_eq!(
+        output,
+        vec!("tar -xf backups/v1.2/archive.tar.gz -C backups/v1.2/archive")
+    );
+}
+
+#[test]
+fn should_format_numbers_and_compute_offsets() {
+    let output = execute_with(
+        &[
+            "-d",
+            "render",
+            "frame_{0:04}.png",
+            "--offset={#:-1:*100}",
+            "{1:>4}",
+        ],
+        "7 a\n12 bc",
+    );
+    assert_eq!(
+        output,
+        vec!(
+            "render frame_0007.png --offset=0 '   a'",
+            "render frame_0012.png --offset=100 '  bc'"
+        )
+    );
+}
+
+#[test]
+fn should_fail_check_when_arithmetic_is_applied_to_text() {
+    let output = execute_with_output(&["--check=abc", "echo", "{0:+1}"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "Failed to resolve sample entry: 'abc' is not a number"
+    );
+}
+
+#[test]
+fn should_fail_when_zipped_arrays_have_different_lengths() {
+    let output = execute_with_output(
+        &["-d", "--combine", "zip", "echo", "{0.}{1.}"],
+        "a.b c.d\na.b c.d.e",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "Failed to resolve entry 2: Cannot zip arrays of different lengths 2 and 3"
+    );
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn should_fail_when_arithmetic_is_applied_to_text() {
+    let output = execute_with_output(&["-d", "echo", "{0:+1}"], "1\nabc");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "Failed to resolve entry 2: 'abc' is not a number"
+    );
+}
+
+#[test]
+fn should_reject_entry_number_operation_without_colon() {
+    let output = execute_with_output(&["-d", "echo", "{#*100}"], "a");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "Invalid template '{#*100}': entry number cannot be split, operations on it follow colon, e.g. {#:*100}"
+    );
+}
+
+#[test]
+fn should_split_by_separators_with_colon_that_are_not_operations() {
+    let output = execute_with(
+        &["-d", "echo", "{0:x1}", "{1x:y1}", "{2':-'1}", "{2:0:-1}"],
+        "a:xb 1x:y2 5:-3",
+    );
+    assert_eq!(output, vec!("echo b 2 3 4"));
+}
+
+#[test]
+fn should_fail_explain_when_template_is_invalid() {
+    let output = execute_with_output(&["--explain", "{0}", "--explain", "{0''}"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("Invalid template: invalid placeholder definition"));
+}
+
+#[test]
+fn should_slice_characters_of_args() {
+    let output = execute_with(
+        &["-d", "mv", "{0}", "{0[0:4]}/{0[5:7]}/{0[11:]}"],
+        "2024-01-05_report.txt",
+    );
+    assert_eq!(output, vec!("mv 2024-01-05_report.txt 2024/01/report.txt"));
+}
+
+#[test]
+fn should_substitute_regex_matches() {
+    let output = execute_with(
+        &["-d", "mv", "{0}", r"{0/s/IMG_([0-9]+)\.JPG/photo-$1.jpg/}"],
+        "IMG_1234.JPG\nnotes.txt",
+    );
+    assert_eq!(
+        output,
+        vec!("mv IMG_1234.JPG photo-1234.jpg", "mv notes.txt notes.txt")
+    );
+}
+
+#[test]
+fn should_substitute_with_braces_in_pattern_and_replacement() {
+    let output = execute_with(
+        &["-d", "mv", "{0}", r"{0/s/^([0-9]{4})-/${1}_/}"],
+        "2024-report.txt",
+    );
+    assert_eq!(output, vec!("mv 2024-report.txt 2024_report.txt"));
+}
+
+#[test]
+fn should_fail_on_unknown_group_in_substitution() {
+    let output = execute_with_output(&["echo", "{0/s/(a)/$1_small/}"], "a");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Invalid template '{0/s/(a)/$1_small/}': unknown capture group '1_small'"));
+}
+
+#[test]
+fn should_fail_on_invalid_regex_in_template() {
+    let output = execute_with_output(&["echo", "{0/s/(a/b/}"], "a");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Invalid template '{0/s/(a/b/}': invalid regex"));
+}
+
+#[test]
+fn should_run_only_filtered_entries() {
+    let output = execute_with(
+        &[
+            "-d",
+            "--filter",
+            "{1}~^prod",
+            "--exclude",
+            "{2}<100",
+            "deploy",
+            "{0}",
+            "{#}",
+        ],
+        "web prod-2 50\ndb prod-1 200\ndb dev-1 300\ncache prod-3 100",
+    );
+    assert_eq!(output, vec!("deploy db 1", "deploy cache 2"));
+}
+
+#[test]
+fn should_count_filtered_out_entries_in_summary() {
+    let output = execute_with_output(&["--filter", "#args==1", "false"], "a\nb c\nd");
+    assert_eq!(output.status.code(), Some(123));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "0 succeeded, 2 failed (entries: 1, 2), 1 filtered out"
+    );
+}
+
+#[test]
+fn should_print_summary_of_successful_run_with_filtered_out_entries() {
+    let output = execute_with_output(&["--filter", "#args==1", "true"], "a\nb c");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "1 succeeded, 1 filtered out"
+    );
+}
+
+#[test]
+fn should_run_unique_entries_sorted_by_key() {
+    let output = execute_with(
+        &["-d", "--unique={0}", "--sort={1}", "echo", "{0}"],
+        "c 10\na 2\nb 1\na 3",
+    );
+    assert_eq!(output, vec!("echo b", "echo a", "echo c"));
+}
+
+#[test]
+fn should_run_command_once_per_group() {
+    let output = execute_with(
+        &["-d", "--group-by", "{0}", "tar", "czf", "{key}.tgz", "{1}"],
+        "host1 a.log\nhost2 b.log\nhost1 c.log",
+    );
+    assert_eq!(
+        output,
+        vec!("tar czf host1.tgz a.log c.log", "tar czf host2.tgz b.log")
+    );
+}
+
+#[test]
+fn should_run_command_for_each_window_of_args() {
+    let output = execute_with(&["-d", "--window", "2", "diff", "{0}", "{1}"], "v1 v2\nv3");
+    assert_eq!(output, vec!("diff v1 v2", "diff v2 v3"));
+}
+
+#[test]
+fn should_drop_args_not_filling_last_window() {
+    let output = execute_with(
+        &["-d", "--window", "2", "--step", "2", "diff", "{0}", "{1}"],
+        "a b c d e",
+    );
+    assert_eq!(output, vec!("diff a b", "diff c d"));
+}
+
+#[test]
+fn should_group_lines_into_entries() {
+    let output = execute_with(
+        &[
+            "-d", "--lines", "2", "-a", ",", "add", "{L0}", "{L1}", "{2}",
+        ],
+        "a,b\nc,d\ne,f\ng",
+    );
+    assert_eq!(output, vec!("add a,b c,d c", "add e,f g g"));
+}
+
+#[test]
+fn should_keep_sources_apart_when_source_entry_has_many_lines() {
+    let args_file = std::env::temp_dir().join("axe_test_sources_args.txt");
+    fs::write(&args_file, "a b\nc\nd\ne").unwrap();
+    let args = [
+        "-d",
+        "-L",
+        "2",
+        "-f",
+        args_file.to_str().unwrap(),
+        "--values",
+        "x",
+        "run",
+        "{S1:1}",
+        "{S2}",
+        "{S2:0}",
+    ];
+    let output = execute_with(&args, "");
+    fs::remove_file(&args_file).unwrap();
+    assert_eq!(output, vec!("run b x x", "run e x x"));
+}
+
+#[test]
+fn should_combine_values_of_many_sources() {
+    let args = [
+        "-d", "--values", "a,b", "--values", "1,2", "run", "{0}", "{S2}",
+    ];
+    let output = execute_with(&args, "");
+    assert_eq!(output, vec!("run a 1", "run a 2", "run b 1", "run b 2"));
+    let output = execute_with(&[&["--link"], &args[..]].concat(), "");
+    assert_eq!(output, vec!("run a 1", "run b 2"));
+}
+
+#[test]
+fn should_keep_record_separator_of_input_as_part_of_arg() {
+    let output = execute_with(&["--plan", "json", "echo", "{0}"], "a\u{1e}b c");
+    assert_eq!(
+        output,
+        vec!(
+            r#"{"entry":1,"input":"a\u001eb c","input_args":["a\u001eb","c"],"cmd":"echo","args":["a\u001eb"]}"#
+        )
+    );
+}
+
 #[test]
 fn should_print_debug_instead_of_running_command() {
     let output = execute_with(&["-d", "echo", "{}"], "a b c\nd e f");
     assert_eq!(output, vec!("echo a b c", "echo d e f"));
 }
 
+#[test]
+fn should_quote_args_in_debug_output() {
+    let output = execute_with(&["-d", "-a=;", "echo", "{0}", "{1}"], "a b;it's\n;$x");
+    assert_eq!(output, vec!(r"echo 'a b' 'it'\''s'", "echo '' '$x'"));
+}
+
+#[test]
+fn should_print_each_arg_in_separate_line_in_debug_output() {
+    let output = execute_with(
+        &[
+            "-d",
+            "--debug-format",
+            "lines",
+            "--stdout-to",
+            "{0}.log",
+            "echo",
+            "{1}",
+        ],
+        "a b c",
+    );
+    assert_eq!(output, vec!("echo \\", "  b \\", "  > a.log"));
+}
+
+#[test]
+fn should_print_execution_plan_as_json() {
+    let output = execute_with(
+        &["--plan", "json", "-a=;", "echo", "{1}", "x{0}"],
+        "a b;c\nd;e",
+    );
+    assert_eq!(
+        output,
+        vec!(
+            r#"{"entry":1,"input":"a b;c","input_args":["a b","c"],"cmd":"echo","args":["c","xa b"]}"#,
+            r#"{"entry":2,"input":"d;e","input_args":["d","e"],"cmd":"echo","args":["e","xd"]}"#
+        )
+    );
+}
+
 #[test]
 fn should_run_echo_with_all_input_when_no_command_provided() {
     let output = execute_with(&["-d"], "a b c\nd e f");
@@ -68,12 +406,371 @@ fn should_use_arg_file_instead_of_stdin() {
     assert_eq!(read_output_lines(output), vec!["a b c", "d e f"]);
 }
 
+#[test]
+fn should_print_output_redirections_in_debug() {
+    let output = execute_with(
+        &[
+            "-d",
+            "--stdout-to",
+            "out/{0}.log",
+            "--stderr-to",
+            "err.log",
+            "echo",
+        ],
+        "a b",
+    );
+    assert_eq!(output, vec!("echo a b > out/a.log 2> err.log"));
+}
+
+#[test]
+fn should_reject_output_path_template_resolving_to_many_values() {
+    let output = execute_with_output(&["--stdout-to", "out/{}.log", "echo"], "a b");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "Invalid template 'out/{}.log': template resolves to many values while single one is expected, values may be joined e.g. {|join:_}"
+    );
+}
+
+#[test]
+fn should_redirect_output_to_files_resolved_from_template() {
+    let out_dir = std::env::temp_dir().join("axe-stdout-to-test");
+    let _ = fs::remove_dir_all(&out_dir);
+    let stdout_template = format!("{}/{{0.0}}.log", out_dir.display());
+
+    let output = execute_with(
+        &["--stdout-to", &stdout_template, "echo", "{0.1}"],
+        "a.x\nb.y",
+    );
+
+    assert!(output.is_empty());
+    assert_eq!(fs::read_to_string(out_dir.join("a.log")).unwrap(), "x\n");
+    assert_eq!(fs::read_to_string(out_dir.join("b.log")).unwrap(), "y\n");
+}
+
+#[test]
+fn should_write_results_of_each_entry_into_separate_directory() {
+    let results_dir = std::env::temp_dir().join("axe-results-test");
+    let _ = fs::remove_dir_all(&results_dir);
+
+    execute_with(
+        &["--results", results_dir.to_str().unwrap(), "echo", "{1}"],
+        "a b\nc d",
+    );
+
+    let entry_dir = results_dir.join("1_a_b");
+    assert_eq!(fs::read_to_string(entry_dir.join("stdout")).unwrap(), "b\n");
+    assert_eq!(fs::read_to_string(entry_dir.join("stderr")).unwrap(), "");
+    assert_eq!(
+        fs::read_to_string(entry_dir.join("exit_code")).unwrap(),
+        "0\n"
+    );
+    assert_eq!(
+        fs::read_to_string(entry_dir.join("cmdline")).unwrap(),
+        "echo b\n"
+    );
+    assert!(entry_dir.join("start_time").exists());
+    assert!(entry_dir.join("duration").exists());
+    assert_eq!(
+        fs::read_to_string(results_dir.join("2_c_d").join("stdout")).unwrap(),
+        "d\n"
+    );
+}
+
+#[test]
+fn should_log_jobs_and_resume_only_not_completed_entries() {
+    let joblog = std::env::temp_dir().join("axe-joblog-test.tsv");
+    let _ = fs::remove_file(&joblog);
+    let joblog_path = joblog.to_str().unwrap();
+
+    execute_with(
+        &["--joblog", joblog_path, "ls", "{0}"],
+        "Cargo.toml\nmissing-file",
+    );
+    let log = fs::read_to_string(&joblog).unwrap();
+    let records: Vec<Vec<&str>> = log.lines().map(|l| l.split('\t').collect()).collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records[0],
+        vec!["Seq", "Starttime", "JobRuntime", "Exitval", "Command"]
+    );
+    assert_eq!(
+        (records[1][0], records[1][3], records[1][4]),
+        ("1", "0", "ls Cargo.toml")
+    );
+    assert_eq!((records[2][0], records[2][4]), ("2", "ls missing-file"));
+    assert_ne!(records[2][3], "0");
+
+    let output = execute_with(
+        &["-d", "--joblog", joblog_path, "--resume", "ls", "{0}"],
+        "Cargo.toml\nmissing-file",
+    );
+    assert_eq!(output, vec!("ls missing-file"));
+
+    //Entries are matched by command, not by their number
+    let output = execute_with_output(
+        &["--joblog", joblog_path, "--resume", "--sort", "ls", "{0}"],
+        "missing-file\nREADME.md\nCargo.toml",
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .collect::<Vec<&str>>(),
+        vec!("README.md")
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().lines().last(),
+        Some("1 succeeded, 1 failed (entries: 3), 1 already completed")
+    );
+}
+
+#[test]
+fn should_terminate_command_exceeding_timeout_and_report_it() {
+    let started = Instant::now();
+    let output = execute_with_output(&["--timeout", "200ms", "sleep", "{0}"], "10\n0");
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(output.status.code(), Some(123));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "1 succeeded, 1 timed out (entries: 1)"
+    );
+}
+
+#[test]
+fn should_retry_failed_command() {
+    let output = execute_with_output(&["--retries", "2", "--retry-delay", "10ms", "false"], "a");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "Command of entry 1 failed with exit code 1, retrying in 10ms (retry 1 of 2)",
+            "Command of entry 1 failed with exit code 1, retrying in 20ms (retry 2 of 2)",
+            "0 succeeded, 1 failed (entries: 1)",
+        ]
+    );
+}
+
+#[test]
+fn should_not_retry_command_when_exit_code_is_not_listed() {
+    let output = execute_with_output(&["--retries", "2", "--retry-on", "2,3", "false"], "a");
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "0 succeeded, 1 failed (entries: 1)"
+    );
+}
+
+#[test]
+fn should_forward_interrupt_when_not_sent_from_terminal() {
+    let started = Instant::now();
+    //Own process group so axe is never in terminal foreground, even when tests are run from terminal
+    let mut axe_handle = Command::new(env!("CARGO_BIN_EXE_axe"))
+        .args(["--kill-after", "10s", "sleep", "{0}"])
+        .process_group(0)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute axe");
+    axe_handle
+        .stdin
+        .take()
+        .expect("Cannot get stdin for axe process")
+        .write_all(b"10")
+        .expect("Failed to write to axe process stdin");
+    std::thread::sleep(Duration::from_millis(300));
+    Command::new("kill")
+        .args(["-INT", &axe_handle.id().to_string()])
+        .status()
+        .expect("Failed to send signal to axe");
+
+    let output = axe_handle
+        .wait_with_output()
+        .expect("Failed to wait for axe process");
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(output.status.code(), Some(130));
+}
+
+#[test]
+fn should_forward_interrupt_sent_to_axe_in_terminal_foreground() {
+    let (mut controller, mut terminal) = (0, 0);
+    //SAFETY: openpty only writes descriptors of newly opened pseudo terminal into given integers
+    let opened = unsafe {
+        libc::openpty(
+            &mut controller,
+            &mut terminal,
+            ptr::null_mut(),
+            ptr::null(),
+            ptr::null(),
+        )
+    };
+    assert_eq!(opened, 0);
+    let started = Instant::now();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_axe"));
+    command
+        .args(["--kill-after", "10s", "sleep", "{0}"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped());
+    //New session controlled by pseudo terminal puts axe in its foreground process group
+    //SAFETY: only async-signal-safe functions are called between fork and exec
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setsid() < 0 || libc::ioctl(terminal, libc::TIOCSCTTY, 0) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let mut axe_handle = command.spawn().expect("Failed to execute axe");
+    axe_handle
+        .stdin
+        .take()
+        .expect("Cannot get stdin for axe process")
+        .write_all(b"10")
+        .expect("Failed to write to axe process stdin");
+    std::thread::sleep(Duration::from_millis(300));
+    Command::new("kill")
+        .args(["-INT", &axe_handle.id().to_string()])
+        .status()
+        .expect("Failed to send signal to axe");
+
+    let output = axe_handle
+        .wait_with_output()
+        .expect("Failed to wait for axe process");
+    //SAFETY: descriptors were opened by openpty and are not used anymore
+    unsafe {
+        libc::close(controller);
+        libc::close(terminal);
+    }
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(output.status.code(), Some(130));
+}
+
+#[test]
+fn should_stop_retrying_when_signal_is_received_during_backoff() {
+    let started = Instant::now();
+    let mut axe_handle = Command::new(env!("CARGO_BIN_EXE_axe"))
+        .args([
+            "--retries",
+            "3",
+            "--retry-delay",
+            "3s",
+            "sh",
+            "-c",
+            "echo try; exit 1",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute axe");
+    axe_handle
+        .stdin
+        .take()
+        .expect("Cannot get stdin for axe process")
+        .write_all(b"a\nb")
+        .expect("Failed to write to axe process stdin");
+    std::thread::sleep(Duration::from_millis(300));
+    Command::new("kill")
+        .args(["-INT", &axe_handle.id().to_string()])
+        .status()
+        .expect("Failed to send signal to axe");
+
+    let output = axe_handle
+        .wait_with_output()
+        .expect("Failed to wait for axe process");
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(output.status.code(), Some(130));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "try\n");
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .ends_with("0 succeeded, 1 interrupted (entries: 1), 1 not run\n"));
+}
+
+#[test]
+fn should_forward_signal_to_running_command_and_skip_remaining_entries() {
+    let started = Instant::now();
+    let mut axe_handle = Command::new(env!("CARGO_BIN_EXE_axe"))
+        .args(["sleep", "{0}"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute axe");
+    axe_handle
+        .stdin
+        .take()
+        .expect("Cannot get stdin for axe process")
+        .write_all(b"10\n10\n10")
+        .expect("Failed to write to axe process stdin");
+    std::thread::sleep(Duration::from_millis(300));
+    Command::new("kill")
+        .args(["-TERM", &axe_handle.id().to_string()])
+        .status()
+        .expect("Failed to send signal to axe");
+
+    let output = axe_handle
+        .wait_with_output()
+        .expect("Failed to wait for axe process");
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(output.status.code(), Some(143));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "0 succeeded, 1 interrupted (entries: 1), 2 not run"
+    );
+}
+
+#[test]
+fn should_fail_on_invalid_template_before_reading_input() {
+    let mut axe_handle = Command::new(env!("CARGO_BIN_EXE_axe"))
+        .args(["echo", "{0''}"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute axe");
+    //stdin is kept open so axe would hang if it waited for input
+    let _stdin = axe_handle.stdin.take();
+    let started = Instant::now();
+    while axe_handle.try_wait().unwrap().is_none() {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let output = axe_handle.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "Invalid template '{0''}': invalid placeholder definition"
+    );
+}
+
+#[test]
+fn should_check_templates_with_sample_entry() {
+    let output = execute_with_output(&["--check=a.txt b", "cp", "{0}", "{0.0}-{1}"], "");
+    assert!(output.status.success());
+    assert_eq!(read_output_lines(output), vec!["cp a.txt a-b"]);
+
+    let output = execute_with_output(&["--check", "cp", "{0}", "{2}"], "");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let output = execute_with_output(&["--check=a", "cp", "{0}", "{2}"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "Failed to resolve sample entry: Index 2 is out of bounds"
+    );
+}
+
 fn execute_with(args: &[&str], input: &str) -> Vec<String> {
+    read_output_lines(execute_with_output(args, input))
+}
+
+fn execute_with_output(args: &[&str], input: &str) -> Output {
     let mut cargo_handle = Command::new("cargo")
         .args(["run", "-q", "--"])
         .args(args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .spawn()
         .expect("Failed to execute cargo run");
     cargo_handle
@@ -82,10 +779,9 @@ fn execute_with(args: &[&str], input: &str) -> Vec<String> {
         .expect("Cannot get stdin for cargo process")
         .write_all(input.as_bytes())
         .expect("Failed to write to cargo process stdin");
-    let output = cargo_handle
+    cargo_handle
         .wait_with_output()
-        .expect("Failed to wait for cargo process");
-    read_output_lines(output)
+        .expect("Failed to wait for cargo process")
 }
 
 fn read_output_lines(output: Output) -> Vec<String> {